@@ -0,0 +1,40 @@
+use std::{env, fs, path::PathBuf};
+
+/// Exposes the ruff revision the formatter is pinned to as `RUFF_VERSION`.
+///
+/// ruff's crates aren't published, so their own version is a placeholder; the git commit recorded
+/// in the workspace's `Cargo.lock` is what identifies the build. Registry sources fall back to
+/// their version, and `unknown` is used when the lockfile can't be read.
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let lockfile = manifest_dir.join("../Cargo.lock");
+
+    println!("cargo:rerun-if-changed={}", lockfile.display());
+
+    let version = fs::read_to_string(&lockfile)
+        .ok()
+        .and_then(|lockfile| ruff_version(&lockfile))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=RUFF_VERSION={version}");
+}
+
+fn ruff_version(lockfile: &str) -> Option<String> {
+    let package = lockfile
+        .split("[[package]]")
+        .find(|package| package.lines().any(|line| line == r#"name = "ruff_python_formatter""#))?;
+
+    let field = |key: &str| {
+        package.lines().find_map(|line| {
+            line.strip_prefix(key)?.strip_prefix(" = \"")?.strip_suffix('"').map(str::to_string)
+        })
+    };
+
+    match field("source") {
+        Some(source) if source.starts_with("git+") => {
+            let (_, commit) = source.rsplit_once('#')?;
+            Some(commit.chars().take(7).collect())
+        }
+        _ => field("version"),
+    }
+}
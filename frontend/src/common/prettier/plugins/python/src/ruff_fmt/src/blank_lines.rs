@@ -0,0 +1,115 @@
+use ruff_fmt_config::Config as InnerConfig;
+use ruff_python_ast::Stmt;
+use ruff_python_formatter::PyFormatOptions;
+use ruff_python_parser::parse_unchecked_source;
+use ruff_text_size::{Ranged, TextRange, TextSize};
+
+use crate::suppression;
+
+/// Rewrites the number of blank lines between top-level statements of formatted `code` to the
+/// configured `blank_lines_after_imports` and `blank_lines_top_level`.
+///
+/// ruff's formatter has no option for this, so it runs as a pass over its output. The gap after
+/// the last import of a block uses `blank_lines_after_imports`; gaps before or after a function
+/// or class use `blank_lines_top_level`. The gap is the run of blank lines right before the next
+/// statement and its leading comments, so a comment ending the body of the previous definition
+/// stays in it. Gaps inside `# fmt: off` regions are kept, and code with syntax errors is
+/// returned unchanged.
+pub fn apply(code: String, config: &InnerConfig) -> String {
+    let top_level = config.blank_lines_top_level();
+    let after_imports = config.blank_lines_after_imports();
+
+    if top_level.is_none() && after_imports.is_none() {
+        return code;
+    }
+
+    let source_type = PyFormatOptions::from(config.clone()).source_type();
+    let parsed = parse_unchecked_source(&code, source_type);
+
+    if !parsed.errors().is_empty() {
+        return code;
+    }
+
+    let newline = if code.contains("\r\n") { "\r\n" } else { "\n" };
+    let suppressed = suppression::suppressed_ranges(&code, config.clone());
+
+    let mut output = String::with_capacity(code.len());
+    let mut last = 0;
+
+    for pair in parsed.syntax().body.windows(2) {
+        let (prev, next) = (&pair[0], &pair[1]);
+
+        let wanted = if is_import(prev) && !is_import(next) {
+            after_imports
+        } else if is_definition(prev) || is_definition(next) {
+            top_level
+        } else {
+            None
+        };
+
+        let Some(wanted) = wanted else {
+            continue;
+        };
+
+        let (gap_start, gap_end) = gap_before(&code, prev.end(), next.start());
+
+        let gap = TextRange::new(
+            TextSize::try_from(gap_start).unwrap(),
+            TextSize::try_from(gap_end).unwrap(),
+        );
+        if suppressed.iter().any(|range| range.start() <= gap.end() && gap.start() < range.end()) {
+            continue;
+        }
+
+        output.push_str(&code[last..gap_start]);
+        output.push_str(&newline.repeat(wanted.into()));
+        last = gap_end;
+    }
+
+    output.push_str(&code[last..]);
+    output
+}
+
+/// Returns the blank lines directly before the statement starting at `next`, above any comments
+/// at the top level that lead into it, as a byte range that doesn't reach back before the line
+/// after `prev_end`.
+fn gap_before(code: &str, prev_end: TextSize, next: TextSize) -> (usize, usize) {
+    let floor = {
+        let offset = prev_end.to_usize();
+        code[offset..].find('\n').map_or(code.len(), |index| offset + index + 1)
+    };
+
+    let mut end = line_start(code, next.to_usize());
+
+    // Leading comments of `next` are unindented; an indented one belongs to the previous body.
+    while end > floor {
+        let above = line_start(code, end - 1);
+        if !code[above..end].starts_with('#') {
+            break;
+        }
+        end = above;
+    }
+
+    let mut start = end;
+    while start > floor {
+        let above = line_start(code, start - 1);
+        if !code[above..start].trim().is_empty() {
+            break;
+        }
+        start = above;
+    }
+
+    (start, end)
+}
+
+fn line_start(code: &str, offset: usize) -> usize {
+    code[..offset].rfind('\n').map_or(0, |index| index + 1)
+}
+
+fn is_import(stmt: &Stmt) -> bool {
+    matches!(stmt, Stmt::Import(_) | Stmt::ImportFrom(_))
+}
+
+fn is_definition(stmt: &Stmt) -> bool {
+    matches!(stmt, Stmt::FunctionDef(_) | Stmt::ClassDef(_))
+}
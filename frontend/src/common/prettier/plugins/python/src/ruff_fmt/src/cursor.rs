@@ -0,0 +1,80 @@
+use similar::{DiffOp, TextDiff};
+
+use crate::offset;
+
+/// Maps the UTF-16 offset `cursor` in `old` to the position of the same character in `new`.
+///
+/// The texts are diffed line by line first, so the cost grows with the size of the changed hunk
+/// around the cursor rather than with the whole document. A cursor on an unchanged line keeps its
+/// column. Inside a changed hunk the old and new lines are diffed character by character: a cursor
+/// on an unchanged character keeps its place, one inside removed text, such as the indentation of
+/// lines that collapsed onto a single line, moves to where that text used to be in `new`.
+pub fn map_cursor(old: &str, new: &str, cursor: u32) -> Result<u32, String> {
+    let byte = offset::utf16_to_byte(old, cursor)?.to_usize();
+
+    let diff = TextDiff::from_lines(old, new);
+    let old_starts = line_starts(diff.old_slices());
+    let new_starts = line_starts(diff.new_slices());
+
+    // The line holding the cursor; one past the last line if it's at the very end.
+    let line = old_starts.partition_point(|&start| start <= byte) - 1;
+
+    let mapped = match diff.ops().iter().find(|op| op.old_range().contains(&line)) {
+        None => new.len(),
+        Some(&DiffOp::Equal { old_index, new_index, .. }) => {
+            new_starts[new_index + (line - old_index)] + (byte - old_starts[line])
+        }
+        Some(op) => {
+            let (old_range, new_range) = (op.old_range(), op.new_range());
+            let old_hunk = &old[old_starts[old_range.start]..old_starts[old_range.end]];
+            let new_hunk = &new[new_starts[new_range.start]..new_starts[new_range.end]];
+
+            let start = new_starts[new_range.start];
+            start + map_within(old_hunk, new_hunk, byte - old_starts[old_range.start])
+        }
+    };
+
+    Ok(new[..mapped].encode_utf16().count() as u32)
+}
+
+/// The byte offset each of `lines` starts at, followed by the length of the whole text.
+fn line_starts(lines: &[&str]) -> Vec<usize> {
+    let mut starts = Vec::with_capacity(lines.len() + 1);
+    let mut start = 0;
+
+    starts.push(start);
+    for line in lines {
+        start += line.len();
+        starts.push(start);
+    }
+
+    starts
+}
+
+/// Maps the byte offset `byte` in `old` to `new` by diffing them character by character.
+fn map_within(old: &str, new: &str, byte: usize) -> usize {
+    let cursor = old[..byte].chars().count();
+
+    let diff = TextDiff::from_chars(old, new);
+
+    let mapped = diff
+        .ops()
+        .iter()
+        .find_map(|op| match *op {
+            DiffOp::Equal { old_index, new_index, len } if cursor < old_index + len => {
+                Some(new_index + (cursor - old_index))
+            }
+            DiffOp::Delete { old_index, old_len, new_index } if cursor < old_index + old_len => {
+                Some(new_index)
+            }
+            DiffOp::Replace { old_index, old_len, new_index, new_len }
+                if cursor < old_index + old_len =>
+            {
+                Some(new_index + (cursor - old_index).min(new_len))
+            }
+            _ => None,
+        })
+        .unwrap_or_else(|| new.chars().count());
+
+    new.chars().take(mapped).map(char::len_utf8).sum()
+}
@@ -0,0 +1,23 @@
+use std::fmt::Write;
+
+use ruff_python_parser::{parse_unchecked, Mode};
+use ruff_text_size::Ranged;
+
+/// Renders the tokens ruff's lexer produces for `input`, one per line with its byte range and
+/// source text, followed by any parse errors.
+pub fn dump_tokens(input: &str) -> String {
+    let parsed = parse_unchecked(input, Mode::Module);
+
+    let mut output = String::new();
+
+    for token in parsed.tokens().iter() {
+        let range = token.range();
+        writeln!(output, "{:?} {range:?} {:?}", token.kind(), &input[range]).unwrap();
+    }
+
+    for error in parsed.errors() {
+        writeln!(output, "error {:?}: {}", error.location, error.error).unwrap();
+    }
+
+    output
+}
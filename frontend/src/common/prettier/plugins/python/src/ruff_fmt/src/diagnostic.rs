@@ -0,0 +1,112 @@
+use ruff_fmt_config::Config as InnerConfig;
+use ruff_python_formatter::{FormatModuleError, PyFormatOptions};
+use ruff_python_parser::parse_unchecked_source;
+use ruff_text_size::TextSize;
+use serde::Serialize;
+
+use crate::offset;
+
+/// A 1-based position in the document; the column is counted in UTF-16 code units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Location {
+    pub line: u32,
+    pub column: u32,
+}
+
+impl Location {
+    pub fn new(input: &str, offset: TextSize) -> Self {
+        let (line, column) = offset::byte_to_line_column(input, offset);
+
+        Self { line, column }
+    }
+}
+
+/// A formatting failure with the position the editor should highlight.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub message: String,
+    /// 1-based line number.
+    pub line: u32,
+    /// 1-based column, in UTF-16 code units.
+    pub column: u32,
+}
+
+impl Diagnostic {
+    pub fn new(input: &str, message: String, offset: TextSize) -> Self {
+        let (line, column) = offset::byte_to_line_column(input, offset);
+
+        Self { message, line, column }
+    }
+
+    /// Syntax errors point at the offending token; format and print errors have no location and
+    /// are reported at the start of the document.
+    pub fn from_format_error(input: &str, err: &FormatModuleError) -> Self {
+        match err {
+            FormatModuleError::ParseError(err) => {
+                Self::new(input, err.error.to_string(), err.location.start())
+            }
+            err => Self::new(input, err.to_string(), TextSize::default()),
+        }
+    }
+}
+
+/// Renders `err` with the offending line and a caret under the error, similar to rustc:
+///
+/// ```text
+/// Expected an expression at 2:5
+///   |
+/// 2 | y = )
+///   |     ^
+/// ```
+///
+/// Errors without a location are returned as their plain message.
+pub fn code_frame(input: &str, err: &FormatModuleError) -> String {
+    let FormatModuleError::ParseError(err) = err else {
+        return err.to_string();
+    };
+
+    let start = err.location.start().to_usize();
+    let line_start = input[..start].rfind('\n').map_or(0, |index| index + 1);
+    let line_end = input[start..].find(['\r', '\n']).map_or(input.len(), |index| start + index);
+    let end = err.location.end().to_usize().clamp(start, line_end);
+
+    // Keep tabs so the caret lines up however wide the editor renders them.
+    let padding: String =
+        input[line_start..start].chars().map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+    let carets = "^".repeat(input[start..end].chars().count().max(1));
+
+    let Location { line, column } = Location::new(input, err.location.start());
+    let gutter = " ".repeat(line.to_string().len());
+
+    format!(
+        "{} at {line}:{column}\n{gutter} |\n{line} | {}\n{gutter} | {padding}{carets}",
+        err.error,
+        &input[line_start..line_end]
+    )
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SyntaxError {
+    pub message: String,
+    pub start: Location,
+    pub end: Location,
+}
+
+/// Collects every syntax error ruff's error-recovering parser reports for `input`.
+///
+/// The parser resynchronizes after most errors, but one mistake can still hide or cause others,
+/// e.g. an unclosed bracket swallows the rest of the file and yields a single error at its end.
+pub fn syntax_errors(input: &str, config: InnerConfig) -> Vec<SyntaxError> {
+    let source_type = PyFormatOptions::from(config).source_type();
+    let parsed = parse_unchecked_source(input, source_type);
+
+    parsed
+        .errors()
+        .iter()
+        .map(|error| SyntaxError {
+            message: error.error.to_string(),
+            start: Location::new(input, error.location.start()),
+            end: Location::new(input, error.location.end()),
+        })
+        .collect()
+}
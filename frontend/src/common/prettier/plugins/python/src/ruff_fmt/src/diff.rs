@@ -0,0 +1,74 @@
+use serde::Serialize;
+use similar::{DiffTag, TextDiff};
+
+/// Renders a unified diff between `old` and `new`, or an empty string when they are equal.
+pub fn unified_diff(old: &str, new: &str, path: &str) -> String {
+    if old == new {
+        return String::new();
+    }
+
+    TextDiff::from_lines(old, new).unified_diff().header(path, path).to_string()
+}
+
+/// Counts the lines that differ between `old` and `new`. A replaced line counts once, so
+/// reformatting one line into three counts three, not four.
+pub fn changed_lines(old: &str, new: &str) -> usize {
+    TextDiff::from_lines(old, new)
+        .ops()
+        .iter()
+        .filter(|op| op.tag() != DiffTag::Equal)
+        .map(|op| op.old_range().len().max(op.new_range().len()))
+        .sum()
+}
+
+/// Replaces the UTF-16 code units `from..to` of the old text with `insert`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Edit {
+    pub from: u32,
+    pub to: u32,
+    pub insert: String,
+}
+
+/// The replacements that turn `old` into `new`, one per run of changed lines, in document order
+/// and with UTF-16 ranges into `old`, so they can be applied all at once.
+pub fn edits(old: &str, new: &str) -> Vec<Edit> {
+    let old_lines = line_starts(old, |line| line.encode_utf16().count());
+    let new_lines = line_starts(new, str::len);
+
+    let mut edits: Vec<Edit> = vec![];
+    let mut last_end = None;
+
+    for op in TextDiff::from_lines(old, new).ops() {
+        if op.tag() == DiffTag::Equal {
+            continue;
+        }
+
+        let (old_range, new_range) = (op.old_range(), op.new_range());
+        let from = old_lines[old_range.start];
+        let to = old_lines[old_range.end];
+        let insert = &new[new_lines[new_range.start]..new_lines[new_range.end]];
+
+        // `similar` may split one change into a deletion and an insertion at the same spot.
+        match edits.last_mut() {
+            Some(edit) if last_end == Some(from) => {
+                edit.to = to as u32;
+                edit.insert.push_str(insert);
+            }
+            _ => edits.push(Edit { from: from as u32, to: to as u32, insert: insert.to_string() }),
+        }
+
+        last_end = Some(to);
+    }
+
+    edits
+}
+
+/// The offset of every line start in `text`, followed by its end, measured with `len`.
+fn line_starts(text: &str, len: impl Fn(&str) -> usize) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(text.split_inclusive('\n').scan(0, |offset, line| {
+            *offset += len(line);
+            Some(*offset)
+        }))
+        .collect()
+}
@@ -0,0 +1,119 @@
+use ruff_fmt_config::Config as InnerConfig;
+use ruff_python_formatter::{PyFormatOptions, QuoteStyle};
+use ruff_python_parser::{parse_unchecked_source, TokenKind};
+use ruff_text_size::{Ranged, TextRange, TextSize};
+
+use crate::suppression;
+
+/// Rewrites the quotes of the triple-quoted strings in formatted `code` to the configured
+/// `docstring_quote_style`.
+///
+/// ruff always prefers `"""` unless `quote_style` is `"preserve"`, so this runs as a pass over its
+/// output. `"preserve"` pairs the triple-quoted strings of `code` with those of `input` in order
+/// and gives up if their number differs. f-strings and strings whose content would end or
+/// contain the new quotes are left alone, as is code with syntax errors and the regions ruff keeps
+/// verbatim because of `# fmt: off` or `# fmt: skip`.
+pub fn apply(input: &str, code: String, config: &InnerConfig) -> String {
+    let input = input.strip_prefix(crate::BOM).unwrap_or(input);
+    let input_range = TextRange::up_to(TextSize::of(input));
+    let code_range = TextRange::up_to(TextSize::of(&code));
+
+    apply_in_range(input, input_range, code, code_range, config)
+}
+
+/// Like [`apply`], for `code` that is `input` with only `input_range` reformatted into
+/// `code_range`, as a range format produces. Only the strings inside `code_range` are rewritten.
+pub fn apply_in_range(
+    input: &str,
+    input_range: TextRange,
+    code: String,
+    code_range: TextRange,
+    config: &InnerConfig,
+) -> String {
+    let Some(quote_style) = config.docstring_quote_style else {
+        return code;
+    };
+
+    let Some(strings) = triple_quoted_strings(&code, code_range, config) else {
+        return code;
+    };
+
+    let quotes: Vec<char> = match quote_style {
+        QuoteStyle::Single => vec!['\''; strings.len()],
+        QuoteStyle::Double => vec!['"'; strings.len()],
+        QuoteStyle::Preserve => match triple_quoted_strings(input, input_range, config) {
+            Some(original) if original.len() == strings.len() => {
+                original.iter().map(|string| string.quote).collect()
+            }
+            _ => return code,
+        },
+    };
+
+    let suppressed = suppression::suppressed_ranges(&code, config.clone());
+
+    let mut output = String::with_capacity(code.len());
+    let mut last = 0;
+
+    for (string, quote) in strings.iter().zip(quotes) {
+        let start = string.range.start().to_usize();
+        let end = string.range.end().to_usize();
+        let body = &code[start + string.prefix_len + 3..end - 3];
+
+        let delimiter = quote.to_string().repeat(3);
+
+        if string.quote == quote
+            || body.contains(&delimiter)
+            || body.ends_with(quote)
+            || suppressed.iter().any(|range| {
+                range.start() < string.range.end() && string.range.start() < range.end()
+            })
+        {
+            continue;
+        }
+
+        output.push_str(&code[last..start + string.prefix_len]);
+        output.push_str(&delimiter);
+        output.push_str(body);
+        output.push_str(&delimiter);
+        last = end;
+    }
+
+    output.push_str(&code[last..]);
+    output
+}
+
+struct TripleQuotedString {
+    range: TextRange,
+    prefix_len: usize,
+    quote: char,
+}
+
+fn triple_quoted_strings(
+    source: &str,
+    range: TextRange,
+    config: &InnerConfig,
+) -> Option<Vec<TripleQuotedString>> {
+    let source_type = PyFormatOptions::from(config.clone()).source_type();
+    let parsed = parse_unchecked_source(source, source_type);
+
+    if !parsed.errors().is_empty() {
+        return None;
+    }
+
+    let strings = parsed
+        .tokens()
+        .iter()
+        .filter(|token| token.kind() == TokenKind::String && range.contains_range(token.range()))
+        .filter_map(|token| {
+            let text = &source[token.range()];
+            let prefix_len = text.find(['\'', '"'])?;
+            let quote = text[prefix_len..].chars().next()?;
+
+            text[prefix_len..]
+                .starts_with(&quote.to_string().repeat(3))
+                .then_some(TripleQuotedString { range: token.range(), prefix_len, quote })
+        })
+        .collect();
+
+    Some(strings)
+}
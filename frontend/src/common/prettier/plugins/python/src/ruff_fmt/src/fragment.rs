@@ -0,0 +1,88 @@
+use std::num::NonZeroU16;
+
+use ruff_fmt_config::Config as InnerConfig;
+use ruff_python_formatter::PyFormatOptions;
+use ruff_python_parser::parse_unchecked_source;
+
+use crate::format_with_config;
+
+/// Formats a selection that isn't necessarily a whole module: a bare expression such as
+/// `foo( a ,b )` or a run of statements cut out of an indented block.
+///
+/// The common indentation is stripped before formatting and put back afterwards, with the line
+/// width reduced by it so the re-indented lines still fit. A fragment that ends in a block header
+/// without a body, like `if x:`, is rejected with an error.
+pub fn format_fragment(input: &str, config: InnerConfig) -> Result<String, String> {
+    if input.trim().is_empty() {
+        return Ok(input.to_string());
+    }
+
+    let indent = common_indent(input);
+    let dedented: String = input
+        .split_inclusive('\n')
+        .map(|line| {
+            line.strip_prefix(indent).unwrap_or_else(|| line.trim_start_matches([' ', '\t']))
+        })
+        .collect();
+
+    let options = PyFormatOptions::from(config.clone());
+    let parsed = parse_unchecked_source(&dedented, options.source_type());
+
+    if !parsed.errors().is_empty() {
+        if let Some(header) = incomplete_block_header(&dedented) {
+            return Err(format!(
+                "the fragment ends with an incomplete block: `{header}` has no body"
+            ));
+        }
+    }
+
+    let line_width = options.line_width().value().saturating_sub(indent.len() as u16).max(1);
+    let config = config.with_line_width(NonZeroU16::new(line_width).unwrap());
+
+    let mut code = format_with_config(&dedented, config)?;
+
+    if !input.ends_with('\n') && code.ends_with('\n') {
+        let end = code.trim_end_matches(['\r', '\n']).len();
+        code.truncate(end);
+    }
+
+    if indent.is_empty() {
+        return Ok(code);
+    }
+
+    Ok(code
+        .split_inclusive('\n')
+        .map(
+            |line| {
+                if line.trim().is_empty() {
+                    line.to_string()
+                } else {
+                    format!("{indent}{line}")
+                }
+            },
+        )
+        .collect())
+}
+
+/// The leading whitespace shared by all non-blank lines.
+fn common_indent(input: &str) -> &str {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| &line[..line.len() - line.trim_start_matches([' ', '\t']).len()])
+        .reduce(|common, indent| {
+            let len = common.bytes().zip(indent.bytes()).take_while(|(a, b)| a == b).count();
+            &common[..len]
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the last line if it is a block header such as `if x:` that is missing its body.
+fn incomplete_block_header(input: &str) -> Option<&str> {
+    let last = input.lines().rev().find(|line| {
+        let line = line.trim();
+        !line.is_empty() && !line.starts_with('#')
+    })?;
+
+    last.trim_end().ends_with(':').then(|| last.trim())
+}
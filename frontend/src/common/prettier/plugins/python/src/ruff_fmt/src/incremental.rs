@@ -0,0 +1,53 @@
+use ruff_fmt_config::Config as InnerConfig;
+use serde::Deserialize;
+
+use crate::{format_range_with_config, offset};
+
+/// A 1-based, inclusive range of lines the editor reports as edited.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub struct LineRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+/// Formats the statements enclosing each of `dirty` and returns the whole document.
+///
+/// Overlapping and adjacent ranges are merged first. The ranges are then formatted from the
+/// bottom of the document up, so formatting one never shifts the lines of those still to come.
+/// Each step is a [`format_range_with_config`], so the output is valid Python even when the
+/// widened statements of two ranges end up overlapping.
+pub fn format_incremental(
+    input: &str,
+    dirty: Vec<LineRange>,
+    config: InnerConfig,
+) -> Result<String, String> {
+    if let Some(range) = dirty.iter().find(|range| range.start > range.end) {
+        return Err(format!("line range start {} is after range end {}", range.start, range.end));
+    }
+
+    let mut code = input.to_string();
+
+    for range in merge(dirty).into_iter().rev() {
+        let range = offset::lines_to_range(&code, range.start, range.end);
+        code = format_range_with_config(&code, range, config.clone())?;
+    }
+
+    Ok(code)
+}
+
+fn merge(mut ranges: Vec<LineRange>) -> Vec<LineRange> {
+    ranges.sort_by_key(|range| range.start);
+
+    let mut merged: Vec<LineRange> = Vec::with_capacity(ranges.len());
+
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end.saturating_add(1) => {
+                last.end = last.end.max(range.end);
+            }
+            _ => merged.push(range),
+        }
+    }
+
+    merged
+}
@@ -1,11 +1,751 @@
+mod blank_lines;
+mod cursor;
+mod debug;
+mod diagnostic;
+mod diff;
+mod docstring_quotes;
+mod fragment;
+mod incremental;
+#[cfg(feature = "lint")]
+mod lint;
+mod min_version;
+mod normalize;
+mod notebook;
+mod numeric_literals;
+mod offset;
+mod partial;
+mod subscript_comma;
+mod suppression;
 #[cfg(test)]
 mod test;
 
+use std::borrow::Cow;
+
+use diagnostic::Diagnostic;
+use offset::ByteRange;
 use ruff_fmt_config::Config as InnerConfig;
 use ruff_python_formatter::format_module_source;
+use ruff_text_size::{TextRange, TextSize};
+use serde::{Deserialize, Serialize};
+
+const BOM: char = '\u{feff}';
 
+/// Routes Rust panics to `console.error` with their message and location instead of the bare
+/// "unreachable executed" error. Runs automatically when the module is instantiated.
+///
+/// A panic still can't be turned into an error: `wasm32-unknown-unknown` builds with
+/// `panic = "abort"`, so it traps and leaves the instance unusable. Create a new instance, or
+/// run the module in a worker that can be restarted, after one.
+#[wasm_bindgen(start)]
+pub fn init() {
+    console_error_panic_hook::set_once();
+}
+
+/// Returns the version of ruff this module was built against, for bug reports.
+///
+/// This is the short commit of the ruff revision in `Cargo.lock`, e.g. `"27e9d1f"`, or
+/// `"unknown"` if the build couldn't read it.
+#[wasm_bindgen]
+pub fn version() -> String {
+    env!("RUFF_VERSION").to_string()
+}
+
+/// Formats `input` as a Python module.
+///
+/// A `# ruff: line-length = 120` or `# ruff: indent-width = 2` comment before the first line of
+/// code overrides the corresponding option for this file only; other `# ruff:` keys are ignored.
 #[wasm_bindgen]
 pub fn format(input: &str, path: Option<String>, config: Option<Config>) -> Result<String, String> {
+    Formatter::new(config)?.format(input, path)
+}
+
+/// Holds a parsed [`Config`] so formatting on every save doesn't convert it from JS each time.
+#[wasm_bindgen]
+pub struct Formatter {
+    config: InnerConfig,
+}
+
+#[wasm_bindgen]
+impl Formatter {
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: Option<Config>) -> Result<Formatter, String> {
+        parse_config(None, config).map(Self::from_config)
+    }
+
+    /// Same as the free [`format`] function, using the config given to the constructor.
+    pub fn format(&self, input: &str, path: Option<String>) -> Result<String, String> {
+        let config = match path {
+            Some(path) => self.config.clone().with_path(path),
+            None => self.config.clone(),
+        };
+
+        format_with_config(input, config)
+    }
+}
+
+impl Formatter {
+    pub fn from_config(config: InnerConfig) -> Self {
+        Self { config }
+    }
+}
+
+/// Like [`format`], but passes non-fatal warnings, such as clamped config values or the
+/// instability of `preview`, to `warn` instead of `console.warn`.
+#[wasm_bindgen]
+pub fn format_verbose(
+    input: &str,
+    path: Option<String>,
+    config: Option<Config>,
+    warn: &WarnCallback,
+) -> Result<String, String> {
+    let config = deserialize_config(path, config)?;
+
+    format_verbose_with_config(input, config, |warning| {
+        // A throwing callback shouldn't fail the format.
+        let _ = warn.call(&JsValue::NULL, warning);
+    })
+}
+
+/// Like [`format`], but reports failures as a value instead of throwing.
+///
+/// Returns `{ code, error }` where exactly one of the two is `null`. Syntax errors carry the
+/// 1-based line and column of the offending token.
+#[wasm_bindgen]
+pub fn format_result(
+    input: &str,
+    path: Option<String>,
+    config: Option<Config>,
+) -> Result<FormatResult, String> {
+    let config = parse_config(path, config)?;
+
+    to_js(&format_result_with_config(input, config))
+}
+
+/// Formats every `{ path, content }` in `inputs` with the same config, crossing the WASM boundary
+/// only once.
+///
+/// Returns one `{ path, code?, error? }` per input, in order; a failure in one file doesn't stop
+/// the others.
+#[wasm_bindgen]
+pub fn format_many(inputs: SourceFiles, config: Option<Config>) -> Result<FormattedFiles, String> {
+    let inputs: Vec<SourceFile> =
+        serde_wasm_bindgen::from_value(inputs.into()).map_err(|e| e.to_string())?;
+    let config = parse_config(None, config)?;
+
+    to_js(&format_many_with_config(inputs, config))
+}
+
+/// Checks every `{ path, content }` in `inputs` like `ruff format --check`, without returning the
+/// formatted code.
+///
+/// Returns `{ would_reformat, errored }`: the paths of the files formatting would change, and a
+/// `{ path, error }` for each file that couldn't be formatted, both in input order. The check
+/// passes when both are empty.
+#[wasm_bindgen]
+pub fn check_formatted(
+    inputs: SourceFiles,
+    config: Option<Config>,
+) -> Result<FormatCheckResult, String> {
+    let inputs: Vec<SourceFile> =
+        serde_wasm_bindgen::from_value(inputs.into()).map_err(|e| e.to_string())?;
+    let config = parse_config(None, config)?;
+
+    to_js(&check_formatted_with_config(inputs, config))
+}
+
+/// Formats as much of a document with syntax errors as is safe: the top-level statements before
+/// the first error are formatted and the rest is kept as typed.
+///
+/// Returns `{ code, skipped }` where `skipped` lists the byte ranges left untouched. If the error is
+/// in the very first statement, `code` is `input` unchanged.
+#[wasm_bindgen]
+pub fn format_best_effort(
+    input: &str,
+    path: Option<String>,
+    config: Option<Config>,
+) -> Result<PartialFormatResult, String> {
+    let config = parse_config(path, config)?;
+
+    to_js(&partial::format_best_effort(input, config)?)
+}
+
+/// Formats `input` and reports whether the output differs from it, as `{ code, changed }`.
+#[wasm_bindgen]
+pub fn format_with_status(
+    input: &str,
+    path: Option<String>,
+    config: Option<Config>,
+) -> Result<FormatStatus, String> {
+    let config = parse_config(path, config)?;
+
+    to_js(&format_with_status_with_config(input, config)?)
+}
+
+/// Formats `input` and returns `{ code, lines_changed, bytes_before, bytes_after }`, enough for a
+/// "N lines reformatted" message without diffing on the JS side.
+#[wasm_bindgen]
+pub fn format_stats(
+    input: &str,
+    path: Option<String>,
+    config: Option<Config>,
+) -> Result<FormatStats, String> {
+    let config = parse_config(path, config)?;
+
+    to_js(&format_stats_with_config(input, config)?)
+}
+
+/// Formats `input` and returns the changes as `{ from, to, insert }` replacements, so an editor can
+/// apply them without replacing the whole document.
+///
+/// `from` and `to` are UTF-16 code unit offsets into `input`, as CodeMirror counts them, so the
+/// result can be passed to `view.dispatch({ changes })` as is. The edits are sorted, don't overlap
+/// and are meant to be applied together; each covers a run of changed lines.
+#[wasm_bindgen]
+pub fn format_edits(
+    input: &str,
+    path: Option<String>,
+    config: Option<Config>,
+) -> Result<FormatEdits, String> {
+    let config = parse_config(path, config)?;
+
+    to_js(&format_edits_with_config(input, config)?)
+}
+
+/// Formats `input` and carries the editor cursor over to the formatted text.
+///
+/// `cursor` and the returned `cursor` are UTF-16 code unit offsets. Returns `{ code, cursor }`.
+///
+/// `cursor` indexes `input` and the returned `cursor` indexes `code`, so a `\r\n` counts as two
+/// units in whichever of them uses CRLF. Pass the document with the line separators it has on
+/// disk: mapping from a view that joins lines with `\n` drifts by one unit per line.
+#[wasm_bindgen]
+pub fn format_with_cursor(
+    input: &str,
+    cursor: u32,
+    path: Option<String>,
+    config: Option<Config>,
+) -> Result<CursorFormatResult, String> {
+    let config = parse_config(path, config)?;
+
+    to_js(&format_with_cursor_with_config(input, cursor, config)?)
+}
+
+/// Formats `input` and reports the regions kept verbatim by `# fmt: off` / `# fmt: on` and
+/// `# fmt: skip` comments, as `{ code, skipped_ranges }`.
+///
+/// `skipped_ranges` are byte ranges into `input`. An unterminated `# fmt: off` runs to the end of
+/// the file.
+#[wasm_bindgen]
+pub fn format_with_skips(
+    input: &str,
+    path: Option<String>,
+    config: Option<Config>,
+) -> Result<SkipsFormatResult, String> {
+    let config = parse_config(path, config)?;
+
+    to_js(&format_with_skips_with_config(input, config)?)
+}
+
+/// Returns whether formatting `input` would leave it byte-for-byte unchanged.
+#[wasm_bindgen]
+pub fn is_formatted(
+    input: &str,
+    path: Option<String>,
+    config: Option<Config>,
+) -> Result<bool, String> {
+    let config = parse_config(path, config)?;
+
+    is_formatted_with_config(input, config)
+}
+
+/// Returns a unified diff between `input` and its formatted form, or `""` if nothing changes.
+///
+/// The `---`/`+++` headers use `path`, falling back to `stdin`.
+#[wasm_bindgen]
+pub fn format_diff(
+    input: &str,
+    path: Option<String>,
+    config: Option<Config>,
+) -> Result<String, String> {
+    let config = parse_config(path, config)?;
+
+    format_diff_with_config(input, config)
+}
+
+/// Formats a selection that may not be a complete module, such as a bare expression or an
+/// indented run of statements, keeping its original indentation.
+#[wasm_bindgen]
+pub fn format_fragment(input: &str, config: Option<Config>) -> Result<String, String> {
+    let config = parse_config(None, config)?;
+
+    fragment::format_fragment(input, config)
+}
+
+/// Formats a Jupyter notebook cell, keeping IPython magics and shell escapes verbatim.
+#[wasm_bindgen]
+pub fn format_cell(input: &str, config: Option<Config>) -> Result<String, String> {
+    let config = parse_config(None, config)?;
+
+    notebook::format_cell(input, config)
+}
+
+/// Returns every syntax error in `input` as `{ message, start, end }`, not just the first one.
+///
+/// Recovery is best effort: an error the parser can't recover from, such as an unclosed bracket,
+/// is reported once and may hide the errors after it.
+#[wasm_bindgen]
+pub fn syntax_errors(input: &str, path: Option<String>) -> Result<SyntaxErrors, String> {
+    let config = parse_config(path, None)?;
+
+    to_js(&diagnostic::syntax_errors(input, config))
+}
+
+/// Returns the oldest `target_version` whose syntax can parse `input`, e.g. `"py310"` for a file
+/// with a `match` statement, or `"py37"` if nothing version-specific is used.
+#[wasm_bindgen]
+pub fn detect_min_version(input: &str) -> Result<String, String> {
+    min_version::detect_min_version(input).map(|version| version.as_str().to_string())
+}
+
+/// Dumps ruff's lexer tokens for `input` with their byte ranges, for diagnosing surprising
+/// formatting results, e.g. from the browser console.
+#[wasm_bindgen]
+pub fn debug_tokens(input: &str) -> Result<String, String> {
+    Ok(debug::dump_tokens(input))
+}
+
+/// Builds a [`Config`] from the `[tool.ruff]` and `[tool.ruff.format]` tables of a
+/// `pyproject.toml`, ready to be passed back into [`format`].
+#[wasm_bindgen]
+pub fn config_from_pyproject(toml: &str) -> Result<Config, String> {
+    let config = InnerConfig::from_pyproject(toml)?;
+
+    serde_wasm_bindgen::to_value(&config).map(JsValue::unchecked_into).map_err(|e| e.to_string())
+}
+
+/// Returns the value of every option when left unset, so a settings UI can show the defaults of
+/// the bundled ruff version instead of hardcoding them.
+#[wasm_bindgen]
+pub fn default_config() -> Result<Config, String> {
+    serde_wasm_bindgen::to_value(&InnerConfig::defaults())
+        .map(JsValue::unchecked_into)
+        .map_err(|e| e.to_string())
+}
+
+/// Returns a JSON Schema (draft 2020-12) describing every `Config` field, its allowed values and
+/// its default, for validating settings before they are passed in.
+#[wasm_bindgen]
+pub fn config_schema() -> String {
+    InnerConfig::schema().to_string()
+}
+
+/// Checks `config` for out-of-range or unknown values, e.g. an `indent_width` of `0`, so a
+/// settings UI can reject it before it reaches the formatter.
+///
+/// The error has one line per invalid field.
+#[wasm_bindgen]
+pub fn validate_config(config: Config) -> Result<(), String> {
+    let value: serde_json::Value =
+        serde_wasm_bindgen::from_value(config.into()).map_err(|e| e.to_string())?;
+
+    InnerConfig::validate(&value)
+}
+
+/// Only normalizes line endings (per `line_ending`), trailing whitespace and the final newline,
+/// without reflowing or re-quoting anything.
+#[wasm_bindgen]
+pub fn normalize_only(input: &str, config: Option<Config>) -> Result<String, String> {
+    let config = parse_config(None, config)?;
+
+    Ok(normalize::normalize(input, &config))
+}
+
+/// Sorts and groups the imports of `input` using ruff's isort implementation.
+///
+/// Honors `known_first_party` and `combine_as_imports`; the rest of the module is left as is.
+#[cfg(feature = "lint")]
+#[wasm_bindgen]
+pub fn organize_imports(
+    input: &str,
+    path: Option<String>,
+    config: Option<Config>,
+) -> Result<String, String> {
+    let config = parse_config(path, config)?;
+
+    lint::organize_imports(input, &config)
+}
+
+/// Lints `input` and returns ruff's diagnostics.
+///
+/// Rules are picked with `select` and `ignore`; without `select` ruff's defaults apply.
+#[cfg(feature = "lint")]
+#[wasm_bindgen]
+pub fn check(
+    input: &str,
+    path: Option<String>,
+    config: Option<Config>,
+) -> Result<LintDiagnostics, String> {
+    let config = parse_config(path, config)?;
+
+    to_js(&lint::check(input, &config)?)
+}
+
+/// Applies ruff's autofixes and returns `{ code, applied }`, where `applied` counts the fixes made
+/// per rule code.
+///
+/// Uses the same `select` and `ignore` rules as [`check`]. Unsafe fixes are skipped unless
+/// `unsafe_fixes` is set.
+#[cfg(feature = "lint")]
+#[wasm_bindgen]
+pub fn fix(input: &str, path: Option<String>, config: Option<Config>) -> Result<FixResult, String> {
+    let config = parse_config(path, config)?;
+
+    to_js(&lint::fix(input, &config)?)
+}
+
+/// Whether ruff has an autofix for the lint rule `code`, such as `"F401"`, for deciding where to
+/// offer a quick fix. Returns `false` for unknown codes.
+#[cfg(feature = "lint")]
+#[wasm_bindgen]
+pub fn is_fixable(code: &str) -> bool {
+    lint::is_fixable(code)
+}
+
+/// Formats the statements overlapping `start..end` and returns the whole document with only that
+/// region rewritten.
+///
+/// `start` and `end` are UTF-16 code unit offsets, matching the positions CodeMirror reports.
+/// Like the cursor of [`format_with_cursor`], they index `input` as passed, where a `\r\n` is two
+/// code units.
+/// The range is widened to the enclosing statements so the result is always valid Python, and the
+/// code outside of it, including its indentation, is left untouched.
+#[wasm_bindgen]
+pub fn format_range(
+    input: &str,
+    start: u32,
+    end: u32,
+    path: Option<String>,
+    config: Option<Config>,
+) -> Result<String, String> {
+    if start > end {
+        return Err(format!("range start {start} is after range end {end}"));
+    }
+
+    let config = parse_config(path, config)?;
+
+    let start = offset::utf16_to_byte(input, start)?;
+    let end = offset::utf16_to_byte(input, end)?;
+
+    format_range_with_config(input, TextRange::new(start, end), config)
+}
+
+/// Formats only the statements around the lines an editor reports as changed and returns the
+/// whole document.
+///
+/// `dirty_ranges` is a list of 1-based, inclusive `{ start, end }` line ranges; overlapping ones
+/// are merged. Like [`format_range`], each range is widened to its enclosing statements.
+#[wasm_bindgen]
+pub fn format_incremental(
+    input: &str,
+    dirty_ranges: LineRanges,
+    path: Option<String>,
+    config: Option<Config>,
+) -> Result<String, String> {
+    let dirty_ranges =
+        serde_wasm_bindgen::from_value(dirty_ranges.into()).map_err(|e| e.to_string())?;
+    let config = parse_config(path, config)?;
+
+    incremental::format_incremental(input, dirty_ranges, config)
+}
+
+pub fn format_with_config(input: &str, config: InnerConfig) -> Result<String, String> {
+    let config = config.with_detected_line_ending(input).with_file_overrides(input);
+    let source = prepare_source(input, &config)?;
+
+    let code = format_module_source(&source, config.clone().into())
+        .map(|result| result.into_code())
+        .map_err(|err| diagnostic::code_frame(&source, &err))?;
+
+    Ok(finish_source(input, code, &config))
+}
+
+pub fn format_verbose_with_config(
+    input: &str,
+    config: InnerConfig,
+    mut warn: impl FnMut(&str),
+) -> Result<String, String> {
+    for warning in config.warnings() {
+        warn(&warning);
+    }
+
+    if config.preview == Some(true) {
+        warn("`preview` is enabled, its unstable formatting may change with any ruff upgrade");
+    }
+
+    format_with_config(input, config)
+}
+
+pub fn format_result_with_config(input: &str, config: InnerConfig) -> FormatOutput {
+    let config = config.with_detected_line_ending(input).with_file_overrides(input);
+    let source = match prepare_source(input, &config) {
+        Ok(source) => source,
+        Err(message) => {
+            let error = Diagnostic::new(input, message, TextSize::default());
+            return FormatOutput { code: None, error: Some(error) };
+        }
+    };
+
+    match format_module_source(&source, config.clone().into()) {
+        Ok(printed) => {
+            let code = finish_source(input, printed.into_code(), &config);
+            FormatOutput { code: Some(code), error: None }
+        }
+        Err(err) => {
+            FormatOutput { code: None, error: Some(Diagnostic::from_format_error(&source, &err)) }
+        }
+    }
+}
+
+pub fn format_many_with_config(inputs: Vec<SourceFile>, config: InnerConfig) -> Vec<FormattedFile> {
+    inputs
+        .into_iter()
+        .map(|SourceFile { path, content }| {
+            let config = config.clone().with_path(path.clone());
+
+            match format_with_config(&content, config) {
+                Ok(code) => FormattedFile { path, code: Some(code), error: None },
+                Err(error) => FormattedFile { path, code: None, error: Some(error) },
+            }
+        })
+        .collect()
+}
+
+pub fn check_formatted_with_config(
+    inputs: Vec<SourceFile>,
+    config: InnerConfig,
+) -> FormatCheckOutput {
+    let mut output = FormatCheckOutput::default();
+
+    for SourceFile { path, content } in inputs {
+        let config = config.clone().with_path(path.clone());
+
+        match format_with_config(&content, config) {
+            Ok(code) if code == content => {}
+            Ok(_) => output.would_reformat.push(path),
+            Err(error) => output.errored.push(FormatCheckError { path, error }),
+        }
+    }
+
+    output
+}
+
+pub fn format_with_status_with_config(
+    input: &str,
+    config: InnerConfig,
+) -> Result<FormatStatusOutput, String> {
+    let code = format_with_config(input, config)?;
+    let changed = code != input;
+
+    Ok(FormatStatusOutput { code, changed })
+}
+
+pub fn format_stats_with_config(
+    input: &str,
+    config: InnerConfig,
+) -> Result<FormatStatsOutput, String> {
+    let code = format_with_config(input, config)?;
+
+    Ok(FormatStatsOutput {
+        lines_changed: diff::changed_lines(input, &code),
+        bytes_before: input.len(),
+        bytes_after: code.len(),
+        code,
+    })
+}
+
+pub fn format_edits_with_config(
+    input: &str,
+    config: InnerConfig,
+) -> Result<Vec<diff::Edit>, String> {
+    format_with_config(input, config).map(|code| diff::edits(input, &code))
+}
+
+pub fn format_with_cursor_with_config(
+    input: &str,
+    cursor: u32,
+    config: InnerConfig,
+) -> Result<CursorOutput, String> {
+    let code = format_with_config(input, config)?;
+    let cursor = cursor::map_cursor(input, &code, cursor)?;
+
+    Ok(CursorOutput { code, cursor })
+}
+
+pub fn format_with_skips_with_config(
+    input: &str,
+    config: InnerConfig,
+) -> Result<SkipsOutput, String> {
+    let skipped_ranges = suppression::suppressed_ranges(input, config.clone())
+        .into_iter()
+        .map(ByteRange::from)
+        .collect();
+    let code = format_with_config(input, config)?;
+
+    Ok(SkipsOutput { code, skipped_ranges })
+}
+
+pub fn is_formatted_with_config(input: &str, config: InnerConfig) -> Result<bool, String> {
+    format_with_config(input, config).map(|output| output == input)
+}
+
+pub fn format_diff_with_config(input: &str, config: InnerConfig) -> Result<String, String> {
+    let path = if config.path().is_empty() { "stdin" } else { config.path() }.to_string();
+
+    format_with_config(input, config).map(|output| diff::unified_diff(input, &output, &path))
+}
+
+/// Same as [`format_range`], but `range` is a byte range into `input`.
+pub fn format_range_with_config(
+    input: &str,
+    range: TextRange,
+    config: InnerConfig,
+) -> Result<String, String> {
+    let config = config.with_detected_line_ending(input).with_file_overrides(input);
+
+    let printed = ruff_python_formatter::format_range(input, range, config.clone().into())
+        .map_err(|err| err.to_string())?;
+
+    let source_range = printed.source_range();
+    let printed_range = TextRange::at(source_range.start(), TextSize::of(printed.as_code()));
+
+    let mut output = String::with_capacity(input.len());
+    output.push_str(&input[..source_range.start().to_usize()]);
+    output.push_str(printed.as_code());
+    output.push_str(&input[source_range.end().to_usize()..]);
+
+    let (output, printed_range) = subscript_comma::apply_in_range(output, printed_range, &config);
+    let output =
+        docstring_quotes::apply_in_range(input, source_range, output, printed_range, &config);
+    Ok(numeric_literals::apply_in_range(input, source_range, output, printed_range, &config))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FormatOutput {
+    pub code: Option<String>,
+    pub error: Option<Diagnostic>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FormatStatusOutput {
+    pub code: String,
+    pub changed: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FormatStatsOutput {
+    pub code: String,
+    pub lines_changed: usize,
+    pub bytes_before: usize,
+    pub bytes_after: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CursorOutput {
+    pub code: String,
+    pub cursor: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct SkipsOutput {
+    pub code: String,
+    pub skipped_ranges: Vec<ByteRange>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct SourceFile {
+    pub path: String,
+    pub content: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct FormatCheckOutput {
+    pub would_reformat: Vec<String>,
+    pub errored: Vec<FormatCheckError>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FormatCheckError {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FormattedFile {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Applies the source rewrites that run before the formatter: stripping a UTF-8 BOM and
+/// `organize_imports`, which fails without the `lint` feature.
+fn prepare_source<'a>(input: &'a str, config: &InnerConfig) -> Result<Cow<'a, str>, String> {
+    let input = input.strip_prefix(BOM).unwrap_or(input);
+
+    if config.organize_imports != Some(true) {
+        return Ok(Cow::Borrowed(input));
+    }
+
+    #[cfg(feature = "lint")]
+    let organized = lint::organize_imports(input, config);
+    #[cfg(not(feature = "lint"))]
+    let organized =
+        Err("`organize_imports` needs ruff_fmt built with the `lint` feature".to_string());
+
+    organized.map(Cow::Owned)
+}
+
+/// Applies the output tweaks that run after the formatter: `collapse_single_subscript_comma`, the
+/// blank line options, `docstring_quote_style`, `normalize_numeric_literals`,
+/// `insert_final_newline` and restoring the BOM removed by [`prepare_source`].
+fn finish_source(input: &str, code: String, config: &InnerConfig) -> String {
+    let code = subscript_comma::apply(code, config);
+    let code = blank_lines::apply(code, config);
+    let code = docstring_quotes::apply(input, code, config);
+    let mut code = numeric_literals::apply(input, code, config);
+
+    if config.insert_final_newline == Some(false) && !input.ends_with('\n') {
+        let trimmed = code.trim_end_matches(['\r', '\n']).len();
+        code.truncate(trimmed);
+    }
+
+    if input.starts_with(BOM) {
+        code.insert(0, BOM);
+    }
+
+    code
+}
+
+/// Serializes `value` for JS, turning `None` into `null` rather than `undefined`.
+fn to_js<T: JsCast>(value: &impl Serialize) -> Result<T, String> {
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_missing_as_null(true);
+
+    value.serialize(&serializer).map(JsValue::unchecked_into).map_err(|e| e.to_string())
+}
+
+fn parse_config(path: Option<String>, config: Option<Config>) -> Result<InnerConfig, String> {
+    let config = deserialize_config(path, config)?;
+
+    for warning in config.warnings() {
+        console_warn(&warning);
+    }
+
+    Ok(config)
+}
+
+/// Same as [`parse_config`], but leaves reporting [`InnerConfig::warnings`] to the caller.
+fn deserialize_config(path: Option<String>, config: Option<Config>) -> Result<InnerConfig, String> {
     let mut config: InnerConfig = if let Some(config) = config {
         serde_wasm_bindgen::from_value(config.clone()).map_err(|e| e.to_string())?
     } else {
@@ -16,9 +756,7 @@ pub fn format(input: &str, path: Option<String>, config: Option<Config>) -> Resu
         config = config.with_path(path);
     }
 
-    format_module_source(input, config.into())
-        .map(|result| result.into_code())
-        .map_err(|err| err.to_string())
+    Ok(config)
 }
 
 use wasm_bindgen::prelude::*;
@@ -28,14 +766,271 @@ const TS_Config: &'static str = r#"
 export interface Config {
     indent_style?: "tab" | "space";
     indent_width?: number;
+    /**
+     * How many columns a tab counts as when deciding whether a line fits, 1 to 16. Only used
+     * with `indent_style: "tab"`; defaults to `indent_width` if set, otherwise 8.
+     */
+    tab_width?: number;
     line_width?: number;
-    line_ending?: "lf" | "crlf";
-    quote_style?: "single" | "double";
+    /** `"auto"` keeps the input's line ending if it consistently uses CRLF, otherwise LF. */
+    line_ending?: "lf" | "crlf" | "auto";
+    /** `"preserve"` keeps quotes as written, even if another quote needs fewer escapes. */
+    quote_style?: "single" | "double" | "preserve";
+    /**
+     * Quotes for triple-quoted strings such as docstrings. Defaults to ruff's handling under
+     * `quote_style`, which prefers `"""` unless `quote_style` is `"preserve"`.
+     */
+    docstring_quote_style?: "single" | "double" | "preserve";
     magic_trailing_comma?: "respect" | "ignore";
+    /**
+     * `true` is the same as `magic_trailing_comma: "ignore"`, which wins if both are set and
+     * disagree.
+     */
+    skip_magic_trailing_comma?: boolean;
+    /**
+     * Join single-element subscripts such as `x[a,]` onto one line even if they were exploded,
+     * keeping the comma that makes `a,` a tuple. Other trailing commas follow
+     * `magic_trailing_comma`. Defaults to `false`.
+     */
+    collapse_single_subscript_comma?: boolean;
+    /** Minimum Python version the formatted code must support. Defaults to ruff's default. */
+    target_version?: "py37" | "py38" | "py39" | "py310" | "py311" | "py312" | "py313";
+    /**
+     * Format as a regular module, a `.pyi` stub or notebook code. Overrides what the `path`
+     * passed alongside the config implies; without either, the input is a regular module.
+     */
+    source_type?: "python" | "stub" | "ipynb";
+    /**
+     * Lowercase numeric prefixes, exponents and the `j` suffix and uppercase hex digits, e.g.
+     * `0XFF` to `0xFF` and `1E5J` to `1e5j`. Defaults to `true`; `false` keeps literals as written.
+     */
+    normalize_numeric_literals?: boolean;
+    /** Format code examples and doctests in docstrings. Defaults to `false`. */
+    docstring_code_format?: boolean;
+    /** Line width for code in docstrings; `"dynamic"` fits it within `line_width`. */
+    docstring_code_line_width?: number | "dynamic";
+    /**
+     * Enable ruff's preview formatting style. Preview output is unstable and may change with any
+     * ruff upgrade, so only opt in if occasional reformatting churn is acceptable.
+     */
+    preview?: boolean;
+    /**
+     * Whether the output ends with a newline. Defaults to `true`; `false` keeps the input's
+     * ending, so a file without a trailing newline stays without one.
+     */
+    insert_final_newline?: boolean;
+    /** Blank lines around top-level functions and classes, 1 or 2. Other values are clamped. */
+    blank_lines_top_level?: number;
+    /**
+     * Blank lines after the top-level import block, 1 or 2. Other values are clamped. Only the
+     * formatter uses it; `organize_imports`, `check` and `fix` keep isort's default.
+     */
+    blank_lines_after_imports?: number;
+    /** Sort and group imports before formatting. Needs a build with the `lint` feature. */
+    organize_imports?: boolean;
+    /** Module patterns to treat as first-party when organizing imports. */
+    known_first_party?: string[];
+    /** Merge `as` imports from the same module into a single statement. */
+    combine_as_imports?: boolean;
+    /** Lint rule codes or prefixes to enable, e.g. `["E", "F401"]`. Defaults to ruff's. */
+    select?: string[];
+    /** Lint rule codes or prefixes to disable after `select` is applied. */
+    ignore?: string[];
+    /** Let `fix` apply fixes that may change the code's behavior. Defaults to `false`. */
+    unsafe_fixes?: boolean;
+}"#;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_FormatResult: &'static str = r#"
+export interface FormatError {
+    message: string;
+    /** 1-based line number. */
+    line: number;
+    /** 1-based column, in UTF-16 code units. */
+    column: number;
+}
+
+export interface FormatResult {
+    code: string | null;
+    error: FormatError | null;
+}"#;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_FormatStatus: &'static str = r#"
+export interface FormatStatus {
+    code: string;
+    /** `false` when `code` is identical to the input. */
+    changed: boolean;
+}"#;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_FormatStats: &'static str = r#"
+export interface FormatStats {
+    code: string;
+    /** Lines added, removed or rewritten; a line rewritten in place counts once. */
+    lines_changed: number;
+    /** UTF-8 length of the input. */
+    bytes_before: number;
+    /** UTF-8 length of `code`. */
+    bytes_after: number;
+}"#;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_FormatEdit: &'static str = r#"
+export interface FormatEdit {
+    /** UTF-16 code unit offset into the input. */
+    from: number;
+    /** UTF-16 code unit offset into the input, exclusive. */
+    to: number;
+    insert: string;
+}"#;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_CursorFormatResult: &'static str = r#"
+export interface CursorFormatResult {
+    code: string;
+    /** UTF-16 offset into `code`. */
+    cursor: number;
+}"#;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_PythonSyntaxError: &'static str = r#"
+/**
+ * A position in the input, before any line ending conversion. `\r\n` ends a line like `\n`, so
+ * lines and columns are the same whichever line endings the input uses.
+ */
+export interface Location {
+    /** 1-based line number. */
+    line: number;
+    /** 1-based column, in UTF-16 code units. */
+    column: number;
+}
+
+export interface PythonSyntaxError {
+    message: string;
+    start: Location;
+    end: Location;
+}"#;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_PartialFormatResult: &'static str = r#"
+export interface ByteRange {
+    start: number;
+    end: number;
+}
+
+/** 1-based, inclusive lines. */
+export interface LineRange {
+    start: number;
+    end: number;
+}
+
+export interface PartialFormatResult {
+    code: string;
+    /** Byte ranges of the input that were left unformatted. */
+    skipped: ByteRange[];
+}"#;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_SkipsFormatResult: &'static str = r#"
+export interface SkipsFormatResult {
+    code: string;
+    /** Byte ranges of the input kept verbatim by `# fmt: off` / `# fmt: skip` comments. */
+    skipped_ranges: ByteRange[];
+}"#;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_SourceFile: &'static str = r#"
+export interface SourceFile {
+    path: string;
+    content: string;
+}
+
+export interface FormattedFile {
+    path: string;
+    code?: string;
+    error?: string;
+}
+
+export interface FormatCheckResult {
+    would_reformat: string[];
+    errored: Array<{ path: string; error: string }>;
+}"#;
+
+#[cfg(feature = "lint")]
+#[wasm_bindgen(typescript_custom_section)]
+const TS_LintDiagnostic: &'static str = r#"
+export interface LintDiagnostic {
+    code: string;
+    message: string;
+    start: Location;
+    end: Location;
+    fixable: boolean;
+}
+
+export interface FixResult {
+    code: string;
+    /** How many fixes were applied per rule code, ordered by code. */
+    applied: Array<{ code: string; count: number }>;
 }"#;
 
 #[wasm_bindgen]
 extern "C" {
+    #[wasm_bindgen(js_namespace = console, js_name = warn)]
+    fn console_warn(message: &str);
+
     #[wasm_bindgen(typescript_type = "Config")]
     pub type Config;
+
+    #[wasm_bindgen(typescript_type = "(warning: string) => void")]
+    pub type WarnCallback;
+
+    #[wasm_bindgen(method, catch)]
+    fn call(this: &WarnCallback, context: &JsValue, warning: &str) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(typescript_type = "FormatResult")]
+    pub type FormatResult;
+
+    #[wasm_bindgen(typescript_type = "FormatStatus")]
+    pub type FormatStatus;
+
+    #[wasm_bindgen(typescript_type = "FormatStats")]
+    pub type FormatStats;
+
+    #[wasm_bindgen(typescript_type = "FormatEdit[]")]
+    pub type FormatEdits;
+
+    #[wasm_bindgen(typescript_type = "CursorFormatResult")]
+    pub type CursorFormatResult;
+
+    #[wasm_bindgen(typescript_type = "LineRange[]")]
+    pub type LineRanges;
+
+    #[wasm_bindgen(typescript_type = "PartialFormatResult")]
+    pub type PartialFormatResult;
+
+    #[wasm_bindgen(typescript_type = "SkipsFormatResult")]
+    pub type SkipsFormatResult;
+
+    #[wasm_bindgen(typescript_type = "SourceFile[]")]
+    pub type SourceFiles;
+
+    #[wasm_bindgen(typescript_type = "FormattedFile[]")]
+    pub type FormattedFiles;
+
+    #[wasm_bindgen(typescript_type = "FormatCheckResult")]
+    pub type FormatCheckResult;
+
+    #[wasm_bindgen(typescript_type = "PythonSyntaxError[]")]
+    pub type SyntaxErrors;
+}
+
+#[cfg(feature = "lint")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "LintDiagnostic[]")]
+    pub type LintDiagnostics;
+
+    #[wasm_bindgen(typescript_type = "FixResult")]
+    pub type FixResult;
 }
@@ -0,0 +1,194 @@
+use std::{path::Path, str::FromStr};
+
+use ruff_diagnostics::FixAvailability;
+use ruff_fmt_config::Config as InnerConfig;
+use ruff_linter::{
+    linter::{lint_fix, lint_only, ParseSource},
+    registry::{Rule, RuleSet},
+    rule_selector::{PreviewOptions, RuleSelector},
+    rules::isort::categorize::KnownModules,
+    settings::{flags, types::UnsafeFixes, LinterSettings},
+    source_kind::SourceKind,
+};
+use ruff_python_formatter::PyFormatOptions;
+use ruff_text_size::Ranged;
+use serde::Serialize;
+
+use crate::diagnostic::Location;
+
+/// The rules ruff enables when no `select` is configured.
+const DEFAULT_SELECT: &[&str] = &["E4", "E7", "E9", "F"];
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct LintDiagnostic {
+    /// The rule code, e.g. `F401`, or `syntax-error` for parse errors.
+    pub code: String,
+    pub message: String,
+    pub start: Location,
+    pub end: Location,
+    pub fixable: bool,
+}
+
+/// Lints `input` with the rules picked by `select` and `ignore`.
+pub fn check(input: &str, config: &InnerConfig) -> Result<Vec<LintDiagnostic>, String> {
+    let settings = linter_settings(config)?;
+
+    let source_type = PyFormatOptions::from(config.clone()).source_type();
+    let source_kind = SourceKind::Python(input.to_string());
+
+    let result = lint_only(
+        Path::new("<stdin>"),
+        None,
+        &settings,
+        flags::Noqa::Enabled,
+        &source_kind,
+        source_type,
+        ParseSource::None,
+    );
+
+    let diagnostics = result
+        .messages
+        .into_iter()
+        .map(|message| LintDiagnostic {
+            code: message
+                .rule()
+                .map_or_else(|| "syntax-error".to_string(), |rule| rule.noqa_code().to_string()),
+            message: message.body().to_string(),
+            start: Location::new(input, message.start()),
+            end: Location::new(input, message.end()),
+            fixable: message.fix().is_some(),
+        })
+        .collect();
+
+    Ok(diagnostics)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct AppliedFix {
+    pub code: String,
+    pub count: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FixOutput {
+    pub code: String,
+    /// The fixed rules, ordered by code.
+    pub applied: Vec<AppliedFix>,
+}
+
+/// Applies ruff's fixes for the rules picked by `select` and `ignore`, repeating until no more
+/// apply. Unsafe fixes only run with `unsafe_fixes` enabled.
+pub fn fix(input: &str, config: &InnerConfig) -> Result<FixOutput, String> {
+    let settings = linter_settings(config)?;
+    let unsafe_fixes = if config.unsafe_fixes == Some(true) {
+        UnsafeFixes::Enabled
+    } else {
+        UnsafeFixes::Disabled
+    };
+
+    apply_fixes(input, config, &settings, unsafe_fixes)
+}
+
+/// Whether ruff can fix at least some violations of the rule with the given code, e.g. `F401`.
+/// Unknown codes are not fixable.
+pub fn is_fixable(code: &str) -> bool {
+    Rule::from_code(code).is_ok_and(|rule| rule.fixable() != FixAvailability::None)
+}
+
+/// Sorts and groups the imports of `input` the way `ruff check --select I --fix` would.
+pub fn organize_imports(input: &str, config: &InnerConfig) -> Result<String, String> {
+    let mut settings = LinterSettings::for_rule(Rule::UnsortedImports);
+    apply_isort_config(&mut settings, config)?;
+
+    apply_fixes(input, config, &settings, UnsafeFixes::Disabled).map(|output| output.code)
+}
+
+fn linter_settings(config: &InnerConfig) -> Result<LinterSettings, String> {
+    let mut rules = RuleSet::empty();
+
+    match &config.select {
+        Some(select) => select_rules(&mut rules, select.iter().map(String::as_str), true)?,
+        None => select_rules(&mut rules, DEFAULT_SELECT.iter().copied(), true)?,
+    }
+
+    if let Some(ignore) = &config.ignore {
+        select_rules(&mut rules, ignore.iter().map(String::as_str), false)?;
+    }
+
+    let mut settings = LinterSettings::for_rules(rules.iter());
+    apply_isort_config(&mut settings, config)?;
+
+    Ok(settings)
+}
+
+fn select_rules<'a>(
+    rules: &mut RuleSet,
+    selectors: impl Iterator<Item = &'a str>,
+    enable: bool,
+) -> Result<(), String> {
+    for selector in selectors {
+        let selector = RuleSelector::from_str(selector)
+            .map_err(|err| format!("invalid rule selector `{selector}`: {err}"))?;
+
+        for rule in selector.rules(&PreviewOptions::default()) {
+            if enable {
+                rules.insert(rule);
+            } else {
+                rules.remove(rule);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn apply_isort_config(settings: &mut LinterSettings, config: &InnerConfig) -> Result<(), String> {
+    if let Some(known_first_party) = &config.known_first_party {
+        let first_party = known_first_party
+            .iter()
+            .map(|module| {
+                glob::Pattern::new(module)
+                    .map_err(|err| format!("invalid known_first_party pattern `{module}`: {err}"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        settings.isort.known_modules =
+            KnownModules::new(first_party, vec![], vec![], vec![], Default::default());
+    }
+
+    if let Some(combine_as_imports) = config.combine_as_imports {
+        settings.isort.combine_as_imports = combine_as_imports;
+    }
+
+    Ok(())
+}
+
+fn apply_fixes(
+    input: &str,
+    config: &InnerConfig,
+    settings: &LinterSettings,
+    unsafe_fixes: UnsafeFixes,
+) -> Result<FixOutput, String> {
+    let source_type = PyFormatOptions::from(config.clone()).source_type();
+    let source_kind = SourceKind::Python(input.to_string());
+
+    let result = lint_fix(
+        Path::new("<stdin>"),
+        None,
+        flags::Noqa::Enabled,
+        unsafe_fixes,
+        settings,
+        &source_kind,
+        source_type,
+    )
+    .map_err(|err| err.to_string())?;
+
+    let mut applied: Vec<AppliedFix> = result
+        .fixed
+        .iter()
+        .map(|(rule, count)| AppliedFix { code: rule.noqa_code().to_string(), count: *count })
+        .collect();
+    applied.sort_by(|a, b| a.code.cmp(&b.code));
+
+    Ok(FixOutput { code: result.transformed.source_code().to_string(), applied })
+}
@@ -0,0 +1,176 @@
+use ruff_fmt_config::TargetVersion;
+use ruff_python_ast::{
+    visitor::{self, Visitor},
+    Decorator, Expr, FString, FStringElement, FStringElements, Parameters, Stmt, StmtWith,
+};
+use ruff_python_parser::parse_module;
+use ruff_text_size::Ranged;
+
+/// Returns the oldest Python version whose grammar accepts `input`.
+///
+/// Only syntax is considered, not the standard library or typing features a file uses:
+///
+/// - `py38`: assignment expressions (`:=`), positional-only parameters (`/`) and the f-string
+///   `=` specifier (`f"{x=}"`)
+/// - `py39`: decorators that are arbitrary expressions
+/// - `py310`: `match` statements and parenthesized context managers
+/// - `py311`: `except*`, starred subscripts (`a[*b]`) and starred `*args` annotations
+///   (`*args: *Ts`)
+/// - `py312`: type parameter lists (`def f[T]()`), `type` aliases and f-string replacement
+///   fields that reuse the enclosing quote, hold a backslash or, in a single-quoted f-string,
+///   span lines
+///
+/// Anything else parses with `py37`, the oldest version ruff supports.
+pub fn detect_min_version(input: &str) -> Result<TargetVersion, String> {
+    let parsed = parse_module(input).map_err(|err| err.to_string())?;
+
+    let mut visitor = MinVersionVisitor { source: input, version: TargetVersion::Py37 };
+    visitor.visit_body(&parsed.syntax().body);
+
+    Ok(visitor.version)
+}
+
+struct MinVersionVisitor<'a> {
+    source: &'a str,
+    version: TargetVersion,
+}
+
+impl<'a> MinVersionVisitor<'a> {
+    fn require(&mut self, version: TargetVersion) {
+        self.version = self.version.max(version);
+    }
+
+    /// `with (a as b, c as d):` rather than `with (a) as b:`, i.e. the parentheses close after
+    /// the last item instead of around a single context expression.
+    fn is_parenthesized_with(&self, with: &StmtWith) -> bool {
+        let Some(last) = with.items.last() else {
+            return false;
+        };
+
+        self.source[last.end().to_usize()..]
+            .trim_start_matches(|c: char| c.is_whitespace() || c == ',' || c == '\\')
+            .starts_with(')')
+    }
+
+    /// The quotes `f_string` opens with, e.g. `"` or `'''`.
+    fn f_string_quotes(&self, f_string: &FString) -> &'a str {
+        let text = self.source[f_string.range()].trim_start_matches(|c: char| c.is_alphabetic());
+        let len = if text.starts_with("\"\"\"") || text.starts_with("'''") { 3 } else { 1 };
+
+        &text[..len]
+    }
+
+    fn visit_f_string_elements(&mut self, f_string: &FString, elements: &FStringElements) {
+        let quotes = self.f_string_quotes(f_string);
+
+        for element in elements.iter() {
+            let FStringElement::Expression(element) = element else {
+                continue;
+            };
+
+            if element.debug_text.is_some() {
+                self.require(TargetVersion::Py38);
+            }
+
+            // Before Python 3.12 an f-string was lexed as a plain string first, so its
+            // replacement fields couldn't hold its closing quotes, a backslash or, unless it's
+            // triple-quoted, a line break.
+            let expression = &self.source[element.expression.range()];
+            if expression.contains(quotes)
+                || expression.contains('\\')
+                || (quotes.len() == 1 && expression.contains('\n'))
+            {
+                self.require(TargetVersion::Py312);
+            }
+
+            if let Some(format_spec) = &element.format_spec {
+                self.visit_f_string_elements(f_string, &format_spec.elements);
+            }
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for MinVersionVisitor<'a> {
+    fn visit_stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::Match(_) => self.require(TargetVersion::Py310),
+            Stmt::With(with) if self.is_parenthesized_with(with) => {
+                self.require(TargetVersion::Py310);
+            }
+            Stmt::Try(try_stmt) if try_stmt.is_star => self.require(TargetVersion::Py311),
+            Stmt::TypeAlias(_) => self.require(TargetVersion::Py312),
+            Stmt::FunctionDef(function) if function.type_params.is_some() => {
+                self.require(TargetVersion::Py312);
+            }
+            Stmt::ClassDef(class) if class.type_params.is_some() => {
+                self.require(TargetVersion::Py312);
+            }
+            _ => {}
+        }
+
+        visitor::walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match expr {
+            Expr::Named(_) => self.require(TargetVersion::Py38),
+            Expr::Subscript(subscript) if is_starred_slice(&subscript.slice) => {
+                self.require(TargetVersion::Py311);
+            }
+            _ => {}
+        }
+
+        visitor::walk_expr(self, expr);
+    }
+
+    fn visit_parameters(&mut self, parameters: &'a Parameters) {
+        if !parameters.posonlyargs.is_empty() {
+            self.require(TargetVersion::Py38);
+        }
+
+        let vararg_annotation =
+            parameters.vararg.as_ref().and_then(|vararg| vararg.annotation.as_deref());
+        if vararg_annotation.is_some_and(Expr::is_starred_expr) {
+            self.require(TargetVersion::Py311);
+        }
+
+        visitor::walk_parameters(self, parameters);
+    }
+
+    fn visit_decorator(&mut self, decorator: &'a Decorator) {
+        let expression = match &decorator.expression {
+            Expr::Call(call) => &*call.func,
+            expression => expression,
+        };
+
+        if !is_dotted_name(expression) {
+            self.require(TargetVersion::Py39);
+        }
+
+        visitor::walk_decorator(self, decorator);
+    }
+
+    fn visit_f_string(&mut self, f_string: &'a FString) {
+        self.visit_f_string_elements(f_string, &f_string.elements);
+
+        visitor::walk_f_string(self, f_string);
+    }
+}
+
+/// `a[*b]` or `a[x, *b]`; a parenthesized `a[(*b,)]` was already allowed before Python 3.11.
+fn is_starred_slice(slice: &Expr) -> bool {
+    match slice {
+        Expr::Starred(_) => true,
+        Expr::Tuple(tuple) => !tuple.parenthesized && tuple.elts.iter().any(Expr::is_starred_expr),
+        _ => false,
+    }
+}
+
+/// Before Python 3.9 a decorator had to be a dotted name, optionally called.
+fn is_dotted_name(expr: &Expr) -> bool {
+    match expr {
+        Expr::Name(_) => true,
+        Expr::Attribute(attribute) => is_dotted_name(&attribute.value),
+        _ => false,
+    }
+}
@@ -0,0 +1,66 @@
+use ruff_fmt_config::{Config as InnerConfig, LineEnding};
+use ruff_python_formatter::PyFormatOptions;
+use ruff_python_parser::{parse_unchecked_source, TokenKind};
+use ruff_text_size::{Ranged, TextRange, TextSize};
+
+/// Normalizes line endings, trailing whitespace and the final newline of `input` without running
+/// the formatter, so the layout of the code is left exactly as written.
+///
+/// Whitespace at the end of a line inside a multi-line string is part of the string's value and is
+/// kept. With `insert_final_newline` unset or `true` the document ends with exactly one newline;
+/// with `false` its ending is kept.
+pub fn normalize(input: &str, config: &InnerConfig) -> String {
+    let config = config.clone().with_detected_line_ending(input);
+    let newline = match config.line_ending {
+        Some(LineEnding::CrLf) => "\r\n",
+        _ => "\n",
+    };
+
+    let strings = string_ranges(input, &config);
+
+    let mut output = String::with_capacity(input.len());
+    let mut line_start = 0;
+
+    for line in input.split_inclusive('\n') {
+        let (content, has_newline) = match line.strip_suffix('\n') {
+            Some(content) => (content.strip_suffix('\r').unwrap_or(content), true),
+            None => (line, false),
+        };
+
+        let trimmed = content.trim_end_matches([' ', '\t', '\x0c']);
+        let trim_start = TextSize::try_from(line_start + trimmed.len()).unwrap();
+
+        if strings.iter().any(|range| range.start() <= trim_start && trim_start < range.end()) {
+            output.push_str(content);
+        } else {
+            output.push_str(trimmed);
+        }
+
+        if has_newline {
+            output.push_str(newline);
+        }
+
+        line_start += line.len();
+    }
+
+    if config.insert_final_newline != Some(false) && !output.is_empty() {
+        let end = output.trim_end_matches(['\r', '\n']).len();
+        output.truncate(end);
+        output.push_str(newline);
+    }
+
+    output
+}
+
+/// The ranges of string literal tokens, including the literal parts of f-strings.
+fn string_ranges(input: &str, config: &InnerConfig) -> Vec<TextRange> {
+    let source_type = PyFormatOptions::from(config.clone()).source_type();
+    let parsed = parse_unchecked_source(input, source_type);
+
+    parsed
+        .tokens()
+        .iter()
+        .filter(|token| matches!(token.kind(), TokenKind::String | TokenKind::FStringMiddle))
+        .map(Ranged::range)
+        .collect()
+}
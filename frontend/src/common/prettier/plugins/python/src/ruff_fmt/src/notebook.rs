@@ -0,0 +1,56 @@
+use ruff_fmt_config::{Config as InnerConfig, SourceType};
+
+use crate::format_with_config;
+
+/// Cell magics whose body is still Python and can be formatted. This mirrors the allowlist ruff
+/// uses when it decides which notebook cells to format.
+const PYTHON_CELL_MAGICS: &[&str] =
+    &["capture", "debug", "prun", "pypy", "python", "python3", "time", "timeit"];
+
+/// Formats a single notebook cell.
+///
+/// Line magics (`%matplotlib inline`), shell escapes (`!pip install foo`) and assignments from
+/// them (`x = %timeit f()`) are understood by ruff's IPython parser mode and kept verbatim. A
+/// leading `%%cell` magic is split off and re-attached untouched; cells whose magic switches to
+/// another language (`%%bash`, `%%html`, ...) are returned as is.
+pub fn format_cell(input: &str, config: InnerConfig) -> Result<String, String> {
+    let (magic, body) = split_cell_magic(input);
+
+    if let Some(magic) = magic {
+        let name = magic[2..].split_whitespace().next().unwrap_or_default();
+
+        if !PYTHON_CELL_MAGICS.contains(&name) {
+            return Ok(input.to_string());
+        }
+    }
+
+    if body.trim().is_empty() {
+        return Ok(input.to_string());
+    }
+
+    let mut code = format_with_config(body, config.with_source_type(SourceType::Ipynb))?;
+
+    // Notebook cells usually don't end with a newline, so only keep the one the formatter adds if
+    // the cell had one.
+    if !body.ends_with('\n') && code.ends_with('\n') {
+        let end = code.trim_end_matches(['\r', '\n']).len();
+        code.truncate(end);
+    }
+
+    Ok(match magic {
+        Some(magic) => format!("{magic}{code}"),
+        None => code,
+    })
+}
+
+/// Splits a leading `%%cell` magic line, including its newline, off the rest of the cell.
+fn split_cell_magic(input: &str) -> (Option<&str>, &str) {
+    if !input.starts_with("%%") {
+        return (None, input);
+    }
+
+    match input.find('\n') {
+        Some(index) => (Some(&input[..=index]), &input[index + 1..]),
+        None => (Some(input), ""),
+    }
+}
@@ -0,0 +1,81 @@
+use ruff_fmt_config::Config as InnerConfig;
+use ruff_python_formatter::PyFormatOptions;
+use ruff_python_parser::{parse_unchecked_source, TokenKind};
+use ruff_text_size::{Ranged, TextRange, TextSize};
+
+/// Restores the numeric literals of `input` in formatted `code` when `normalize_numeric_literals`
+/// is `false`.
+///
+/// ruff always lowercases prefixes, exponents and the imaginary suffix (`0XFF` -> `0xFF`,
+/// `1E5` -> `1e5`, `2J` -> `2j`) and uppercases hex digits (`0xff` -> `0xFF`), so this runs as a
+/// pass over its output. The formatter never adds or removes literals, so the literals of `code`
+/// are paired with those of `input` in order; if their number differs, or either has syntax
+/// errors, `code` is returned unchanged.
+pub fn apply(input: &str, code: String, config: &InnerConfig) -> String {
+    let input = input.strip_prefix(crate::BOM).unwrap_or(input);
+    let input_range = TextRange::up_to(TextSize::of(input));
+    let code_range = TextRange::up_to(TextSize::of(&code));
+
+    apply_in_range(input, input_range, code, code_range, config)
+}
+
+/// Like [`apply`], for `code` that is `input` with only `input_range` reformatted into
+/// `code_range`, as a range format produces. Only the literals inside the two ranges are paired.
+pub fn apply_in_range(
+    input: &str,
+    input_range: TextRange,
+    code: String,
+    code_range: TextRange,
+    config: &InnerConfig,
+) -> String {
+    if config.normalize_numeric_literals != Some(false) {
+        return code;
+    }
+
+    let (Some(original), Some(formatted)) =
+        (numeric_literals(input, input_range, config), numeric_literals(&code, code_range, config))
+    else {
+        return code;
+    };
+
+    if original.len() != formatted.len() {
+        return code;
+    }
+
+    let mut output = String::with_capacity(code.len());
+    let mut last = 0;
+
+    for (original, formatted) in original.into_iter().zip(formatted) {
+        output.push_str(&code[last..formatted.start().to_usize()]);
+        output.push_str(&input[original]);
+        last = formatted.end().to_usize();
+    }
+
+    output.push_str(&code[last..]);
+    output
+}
+
+fn numeric_literals(
+    source: &str,
+    range: TextRange,
+    config: &InnerConfig,
+) -> Option<Vec<TextRange>> {
+    let source_type = PyFormatOptions::from(config.clone()).source_type();
+    let parsed = parse_unchecked_source(source, source_type);
+
+    if !parsed.errors().is_empty() {
+        return None;
+    }
+
+    let literals = parsed
+        .tokens()
+        .iter()
+        .filter(|token| {
+            matches!(token.kind(), TokenKind::Int | TokenKind::Float | TokenKind::Complex)
+        })
+        .map(Ranged::range)
+        .filter(|literal| range.contains_range(*literal))
+        .collect();
+
+    Some(literals)
+}
@@ -0,0 +1,70 @@
+use ruff_text_size::{TextRange, TextSize};
+use serde::{Deserialize, Serialize};
+
+/// A `start..end` byte range into a document, as exchanged with JS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ByteRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl From<TextRange> for ByteRange {
+    fn from(range: TextRange) -> Self {
+        Self { start: range.start().into(), end: range.end().into() }
+    }
+}
+
+/// Converts a UTF-16 code unit offset, as reported by CodeMirror, into a byte offset into `input`.
+///
+/// An offset that points into the middle of a surrogate pair is snapped back to the start of the
+/// character. Offsets past the end of `input` are an error.
+pub fn utf16_to_byte(input: &str, offset: u32) -> Result<TextSize, String> {
+    let mut utf16 = 0;
+
+    for (index, ch) in input.char_indices() {
+        utf16 += ch.len_utf16() as u32;
+
+        if offset < utf16 {
+            return Ok(TextSize::try_from(index).unwrap());
+        }
+    }
+
+    if offset == utf16 {
+        Ok(TextSize::of(input))
+    } else {
+        Err(format!("offset {offset} is out of bounds for a document of {utf16} UTF-16 code units"))
+    }
+}
+
+/// Converts a byte offset into `input` into a 1-based `(line, column)` pair.
+///
+/// The column is counted in UTF-16 code units so it lines up with CodeMirror positions.
+pub fn byte_to_line_column(input: &str, offset: TextSize) -> (u32, u32) {
+    let before = &input[..offset.to_usize().min(input.len())];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+    let line = before.matches('\n').count() as u32 + 1;
+    let column = before[line_start..].encode_utf16().count() as u32 + 1;
+
+    (line, column)
+}
+
+/// Converts the 1-based, inclusive lines `start..=end` into the byte range of their content,
+/// without the newline of the last line.
+///
+/// Lines past the end of `input` are clamped to its last line, as an editor may report an edit
+/// that has since been deleted.
+pub fn lines_to_range(input: &str, start: u32, end: u32) -> TextRange {
+    let line_starts: Vec<usize> =
+        std::iter::once(0).chain(input.match_indices('\n').map(|(index, _)| index + 1)).collect();
+    let line_start = |line: u32| {
+        let index = (line.max(1) - 1) as usize;
+        line_starts[index.min(line_starts.len() - 1)]
+    };
+
+    let start = line_start(start);
+    let last_line = line_start(end).max(start);
+    let end = input[last_line..].find(['\r', '\n']).map_or(input.len(), |index| last_line + index);
+
+    TextRange::new(TextSize::try_from(start).unwrap(), TextSize::try_from(end).unwrap())
+}
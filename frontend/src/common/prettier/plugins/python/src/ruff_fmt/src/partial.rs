@@ -0,0 +1,56 @@
+use ruff_fmt_config::Config as InnerConfig;
+use ruff_python_formatter::PyFormatOptions;
+use ruff_python_parser::parse_unchecked_source;
+use ruff_text_size::{Ranged, TextRange, TextSize};
+use serde::Serialize;
+
+use crate::{format_with_config, offset::ByteRange};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PartialOutput {
+    pub code: String,
+    /// Byte ranges of `input` that were left unformatted.
+    pub skipped: Vec<ByteRange>,
+}
+
+/// Formats the longest run of top-level statements that precedes the first syntax error and
+/// leaves everything from there on untouched.
+///
+/// Valid input is formatted in full with nothing skipped. When the error is inside the first
+/// statement there is nothing safe to format and `input` is returned unchanged, with the whole
+/// document reported as skipped.
+///
+/// The prefix goes through the same pipeline as [`format_with_config`], with the line ending
+/// detected from the whole document so it matches the skipped remainder.
+pub fn format_best_effort(input: &str, config: InnerConfig) -> Result<PartialOutput, String> {
+    let config = config.with_detected_line_ending(input);
+    let source_type = PyFormatOptions::from(config.clone()).source_type();
+    let parsed = parse_unchecked_source(input, source_type);
+
+    let Some(error_start) = parsed.errors().iter().map(|error| error.location.start()).min() else {
+        let code = format_with_config(input, config)?;
+        return Ok(PartialOutput { code, skipped: vec![] });
+    };
+
+    let prefix_end = parsed
+        .syntax()
+        .body
+        .iter()
+        .take_while(|stmt| stmt.end() <= error_start)
+        .last()
+        .map_or(TextSize::default(), Ranged::end);
+
+    let skipped = TextRange::new(prefix_end, TextSize::of(input));
+
+    if prefix_end == TextSize::default() {
+        return Ok(PartialOutput { code: input.to_string(), skipped: vec![skipped.into()] });
+    }
+
+    let prefix = format_with_config(&input[..prefix_end.to_usize()], config)?;
+
+    // The newline after the last formatted statement is still part of the skipped remainder.
+    let mut code = prefix.trim_end_matches(['\r', '\n']).to_string();
+    code.push_str(&input[skipped]);
+
+    Ok(PartialOutput { code, skipped: vec![skipped.into()] })
+}
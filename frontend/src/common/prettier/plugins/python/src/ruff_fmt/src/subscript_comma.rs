@@ -0,0 +1,143 @@
+use ruff_fmt_config::Config as InnerConfig;
+use ruff_python_ast::{
+    visitor::{self, Visitor},
+    Expr,
+};
+use ruff_python_formatter::PyFormatOptions;
+use ruff_python_parser::parse_unchecked_source;
+use ruff_text_size::{Ranged, TextRange, TextSize};
+
+use crate::suppression;
+
+/// Joins single-element subscripts that formatted `code` keeps exploded,
+///
+/// ```python
+/// x[
+///     a,
+/// ]
+/// ```
+///
+/// back onto one line as `x[a,]`, when `collapse_single_subscript_comma` is set.
+///
+/// The comma stays: `x[a,]` indexes with the tuple `(a,)`, and dropping it would change the
+/// meaning of the code. Subscripts with more elements, parenthesized tuples and tuple literals
+/// keep following `magic_trailing_comma`. A subscript is only joined if the line still fits in
+/// `line_width` and there are no comments inside its brackets. Subscripts in `# fmt: off` or
+/// `# fmt: skip` regions and code with syntax errors are left unchanged.
+pub fn apply(code: String, config: &InnerConfig) -> String {
+    let range = TextRange::up_to(TextSize::of(&code));
+
+    apply_in_range(code, range, config).0
+}
+
+/// Like [`apply`], but only joins the subscripts inside `range`, as reformatted by a range format.
+/// Returns the code along with `range` shrunk by the joins.
+pub fn apply_in_range(
+    mut code: String,
+    mut range: TextRange,
+    config: &InnerConfig,
+) -> (String, TextRange) {
+    if config.collapse_single_subscript_comma != Some(true) {
+        return (code, range);
+    }
+
+    // An outer subscript can only be joined once the ones nested in it are, and one sharing a line
+    // with a joined one has to be measured again, so repeat until nothing changes.
+    while let Some(collapsed) = collapse(&code, range, config) {
+        let removed = TextSize::of(&code) - TextSize::of(&collapsed);
+        range = TextRange::new(range.start(), range.end() - removed);
+        code = collapsed;
+    }
+
+    (code, range)
+}
+
+/// Joins the subscripts inside `range` that can be joined without first joining one nested in
+/// them or on the same line as them, or returns `None` if there are none.
+fn collapse(code: &str, range: TextRange, config: &InnerConfig) -> Option<String> {
+    let options = PyFormatOptions::from(config.clone());
+    let parsed = parse_unchecked_source(code, options.source_type());
+
+    if !parsed.errors().is_empty() {
+        return None;
+    }
+
+    let suppressed = suppression::suppressed_ranges(code, config.clone());
+
+    let mut finder = SingleElementSubscripts { subscripts: vec![] };
+    finder.visit_body(&parsed.syntax().body);
+    // The brackets of `x[a,][b,]`'s outer subscript come after those of the one it indexes.
+    finder.subscripts.sort_by_key(|(brackets, _)| brackets.start());
+
+    let line_width = usize::from(options.line_width().value());
+
+    let mut output = String::with_capacity(code.len());
+    let mut last = 0;
+
+    for (brackets, element) in finder.subscripts {
+        let (start, end) = (brackets.start().to_usize(), brackets.end().to_usize());
+        let element = &code[element];
+
+        let brackets_text = &code[start..end];
+        let line_start = code[..start].rfind('\n').map_or(0, |index| index + 1);
+
+        // `line_start < last` skips subscripts nested in one that was just joined and those that
+        // would end up on its line, whose width is only known once it is joined.
+        if line_start < last
+            || !range.contains_range(brackets)
+            || !brackets_text.contains('\n')
+            || brackets_text.contains('#')
+            || element.contains('\n')
+            || suppressed
+                .iter()
+                .any(|region| region.start() < brackets.end() && brackets.start() < region.end())
+        {
+            continue;
+        }
+
+        let collapsed = format!("[{element},]");
+
+        let line_end = code[end..].find(['\r', '\n']).map_or(code.len(), |index| end + index);
+        let width = code[line_start..start].chars().count()
+            + collapsed.chars().count()
+            + code[end..line_end].chars().count();
+
+        if width > line_width {
+            continue;
+        }
+
+        output.push_str(&code[last..start]);
+        output.push_str(&collapsed);
+        last = end;
+    }
+
+    if last == 0 {
+        return None;
+    }
+
+    output.push_str(&code[last..]);
+    Some(output)
+}
+
+/// Collects the `[...]` range and element range of every subscript whose slice is an
+/// unparenthesized one-element tuple.
+struct SingleElementSubscripts {
+    subscripts: Vec<(TextRange, TextRange)>,
+}
+
+impl<'a> Visitor<'a> for SingleElementSubscripts {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        if let Expr::Subscript(subscript) = expr {
+            if let Expr::Tuple(tuple) = &*subscript.slice {
+                if let [element] = &*tuple.elts {
+                    if !tuple.parenthesized {
+                        let brackets = TextRange::new(subscript.value.end(), subscript.end());
+                        self.subscripts.push((brackets, element.range()));
+                    }
+                }
+            }
+        }
+
+        visitor::walk_expr(self, expr);
+    }
+}
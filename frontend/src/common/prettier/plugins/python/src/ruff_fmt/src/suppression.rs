@@ -0,0 +1,110 @@
+use ruff_fmt_config::Config as InnerConfig;
+use ruff_python_formatter::PyFormatOptions;
+use ruff_python_parser::{parse_unchecked_source, TokenKind};
+use ruff_text_size::{Ranged, TextRange, TextSize};
+
+/// Finds the regions of `input` the formatter keeps verbatim because of suppression comments.
+///
+/// Like ruff, only `# fmt: off` / `# fmt: on` comments on their own line between statements
+/// count; a trailing `x = 1  # fmt: off` or one inside brackets is ignored. A region starts at
+/// the beginning of the comment's line and ends after the next `# fmt: on` at the same level. If
+/// there is none, it ends with the enclosing block, or at the end of the file at the top level. A
+/// `# fmt: skip` suppresses the statement it trails, from the start of the statement's first line,
+/// and is ignored on its own line or inside brackets. The ranges are byte ranges into `input`, in
+/// source order.
+pub fn suppressed_ranges(input: &str, config: InnerConfig) -> Vec<TextRange> {
+    let source_type = PyFormatOptions::from(config).source_type();
+    let parsed = parse_unchecked_source(input, source_type);
+
+    let mut ranges = vec![];
+    // The start of the open region and the indentation of its `# fmt: off`. Comment lines don't
+    // produce `Indent` tokens, so blocks are told apart by indentation rather than by counting.
+    let mut off: Option<(TextSize, usize)> = None;
+    let mut brackets = 0u32;
+    // Where the current statement's first token is.
+    let mut statement: Option<TextSize> = None;
+
+    for token in parsed.tokens() {
+        match token.kind() {
+            TokenKind::Newline | TokenKind::Indent | TokenKind::Dedent => statement = None,
+            TokenKind::Comment | TokenKind::NonLogicalNewline => {}
+            _ => {
+                statement.get_or_insert(token.start());
+            }
+        }
+
+        match token.kind() {
+            TokenKind::Lpar | TokenKind::Lsqb | TokenKind::Lbrace => brackets += 1,
+            TokenKind::Rpar | TokenKind::Rsqb | TokenKind::Rbrace => {
+                brackets = brackets.saturating_sub(1);
+            }
+            TokenKind::Dedent => {
+                let line_start = line_start(input, token.start());
+
+                if let Some((start, indent)) = off {
+                    if indentation(input, line_start) < indent {
+                        ranges.push(TextRange::new(start, line_start));
+                        off = None;
+                    }
+                }
+            }
+            TokenKind::Comment => {
+                let comment = input[token.range()].trim_start_matches('#').trim();
+                let statement_start = statement.map(|start| line_start(input, start));
+                let line_start = line_start(input, token.start());
+                let code_before =
+                    !input[line_start.to_usize()..token.start().to_usize()].trim().is_empty();
+                let own_line = brackets == 0 && !code_before;
+
+                match off {
+                    None if own_line && is_fmt_off(comment) => {
+                        off = Some((line_start, indentation(input, line_start)));
+                    }
+                    None if brackets == 0 && code_before && is_fmt_skip(comment) => {
+                        let start = statement_start.unwrap_or(line_start);
+                        ranges.push(TextRange::new(start, token.end()));
+                    }
+                    Some((start, indent))
+                        if own_line
+                            && indentation(input, line_start) == indent
+                            && is_fmt_on(comment) =>
+                    {
+                        ranges.push(TextRange::new(start, token.end()));
+                        off = None;
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if let Some((start, _)) = off {
+        ranges.push(TextRange::new(start, TextSize::of(input)));
+    }
+
+    ranges
+}
+
+fn line_start(input: &str, offset: TextSize) -> TextSize {
+    let index = input[..offset.to_usize()].rfind('\n').map_or(0, |index| index + 1);
+    TextSize::try_from(index).unwrap()
+}
+
+/// The width of the leading whitespace of the line starting at `line_start`.
+fn indentation(input: &str, line_start: TextSize) -> usize {
+    let line = &input[line_start.to_usize()..];
+    line.len() - line.trim_start_matches([' ', '\t', '\x0c']).len()
+}
+
+fn is_fmt_off(comment: &str) -> bool {
+    matches!(comment, "fmt: off" | "fmt:off" | "yapf: disable")
+}
+
+fn is_fmt_on(comment: &str) -> bool {
+    matches!(comment, "fmt: on" | "fmt:on" | "yapf: enable")
+}
+
+fn is_fmt_skip(comment: &str) -> bool {
+    comment.ends_with("fmt: skip") || comment.ends_with("fmt:skip")
+}
@@ -1,9 +1,43 @@
 #[cfg(test)]
 mod tests {
-    use std::{fs::File, io::Read, path::PathBuf, str::FromStr};
+    use std::{
+        fs::File,
+        io::Read,
+        num::{NonZeroU16, NonZeroU8},
+        path::PathBuf,
+        str::FromStr,
+    };
     use testing_macros::fixture;
 
-    use crate::format;
+    use ruff_fmt_config::{
+        Config, IndentStyle, LineEnding, SourceType, TargetVersion, INDENT_WIDTH_RANGE,
+        LINE_WIDTH_RANGE, TAB_WIDTH_RANGE,
+    };
+    use ruff_python_formatter::{MagicTrailingComma, PyFormatOptions, QuoteStyle};
+    use ruff_text_size::{TextRange, TextSize};
+    use serde_json::json;
+
+    #[cfg(feature = "lint")]
+    use crate::lint::{check, fix, is_fixable, organize_imports, AppliedFix};
+    use crate::{
+        check_formatted_with_config,
+        cursor::map_cursor,
+        debug::dump_tokens,
+        diagnostic::{syntax_errors, Diagnostic, Location},
+        diff::Edit,
+        format, format_diff_with_config, format_edits_with_config, format_many_with_config,
+        format_range_with_config, format_result_with_config, format_stats_with_config,
+        format_verbose_with_config, format_with_config, format_with_status_with_config,
+        fragment::format_fragment,
+        incremental::{format_incremental, LineRange},
+        is_formatted_with_config,
+        min_version::detect_min_version,
+        normalize::normalize,
+        notebook::format_cell,
+        offset::{utf16_to_byte, ByteRange},
+        partial::format_best_effort,
+        FormatStatsOutput, FormatStatusOutput, FormattedFile, Formatter, SourceFile,
+    };
 
     #[fixture("test_data/**/*.py")]
     #[fixture("test_data/**/*.pyi")]
@@ -23,4 +57,1254 @@ mod tests {
 
         assert_eq!(actual, expect);
     }
+
+    #[test]
+    fn target_version_controls_parenthesized_with() {
+        let input = "with open(\"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\") as a, open(\"bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb\") as b:\n    pass\n";
+
+        let config = Config::default().with_target_version(TargetVersion::Py38);
+        let py38 = format_with_config(input, config).unwrap();
+
+        let config = Config::default().with_target_version(TargetVersion::Py310);
+        let py310 = format_with_config(input, config).unwrap();
+
+        assert!(!py38.starts_with("with (\n"));
+        assert_eq!(
+            py310,
+            "with (\n    open(\"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\") as a,\n    open(\"bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb\") as b,\n):\n    pass\n"
+        );
+    }
+
+    #[test]
+    fn format_range_only_touches_selected_statements() {
+        let input = "x  =  1\ny  =  2\nz  =  3\n";
+        let range = TextRange::new(TextSize::new(9), TextSize::new(11));
+
+        let actual = format_range_with_config(input, range, Config::default()).unwrap();
+
+        assert_eq!(actual, "x  =  1\ny = 2\nz  =  3\n");
+    }
+
+    #[test]
+    fn format_range_keeps_surrounding_indentation() {
+        let input = "def f():\n    a  =  1\n    b  =  [1,2]\n";
+        let range = TextRange::new(TextSize::new(26), TextSize::new(30));
+
+        let actual = format_range_with_config(input, range, Config::default()).unwrap();
+
+        assert_eq!(actual, "def f():\n    a  =  1\n    b = [1, 2]\n");
+    }
+
+    #[test]
+    fn utf16_offsets_map_to_bytes() {
+        let input = "s = \"\u{1F600}\"\nx=1\n";
+
+        assert_eq!(utf16_to_byte(input, 0).unwrap(), TextSize::new(0));
+        assert_eq!(utf16_to_byte(input, 5).unwrap(), TextSize::new(5));
+        // the middle of the surrogate pair snaps back to the start of the emoji
+        assert_eq!(utf16_to_byte(input, 6).unwrap(), TextSize::new(5));
+        assert_eq!(utf16_to_byte(input, 7).unwrap(), TextSize::new(9));
+        assert_eq!(utf16_to_byte(input, 13).unwrap(), TextSize::of(input));
+        assert!(utf16_to_byte(input, 14).is_err());
+    }
+
+    #[test]
+    fn format_result_reports_syntax_error_position() {
+        let actual = format_result_with_config("x = 1\ny = = 2\n", Config::default());
+
+        assert_eq!(actual.code, None);
+
+        let error = actual.error.unwrap();
+        assert_eq!((error.line, error.column), (2, 5));
+    }
+
+    #[test]
+    fn format_result_returns_code_on_success() {
+        let actual = format_result_with_config("x=1\n", Config::default());
+
+        assert_eq!(actual.code.as_deref(), Some("x = 1\n"));
+        assert_eq!(actual.error, None::<Diagnostic>);
+    }
+
+    #[cfg(feature = "lint")]
+    #[test]
+    fn organize_imports_sorts_and_groups() {
+        let input = "import sys\nimport os\n";
+
+        let actual = organize_imports(input, &Config::default()).unwrap();
+
+        assert_eq!(actual, "import os\nimport sys\n");
+    }
+
+    #[cfg(feature = "lint")]
+    #[test]
+    fn organize_imports_honors_known_first_party() {
+        let input = "import foo\nimport requests\n";
+        let config = Config::default().with_known_first_party(vec!["foo".into()]);
+
+        let actual = organize_imports(input, &config).unwrap();
+
+        assert_eq!(actual, "import requests\n\nimport foo\n");
+    }
+
+    #[cfg(feature = "lint")]
+    #[test]
+    fn organize_imports_combines_as_imports() {
+        let input = "from a import b as c\nfrom a import d\n";
+        let config = Config::default().with_combine_as_imports(true);
+
+        let actual = organize_imports(input, &config).unwrap();
+
+        assert_eq!(actual, "from a import b as c, d\n");
+    }
+
+    #[cfg(feature = "lint")]
+    #[test]
+    fn format_runs_organize_imports_when_enabled() {
+        let input = "import sys\nimport os\nx=1\n";
+        let config = Config::default().with_organize_imports(true);
+
+        let actual = format_with_config(input, config).unwrap();
+
+        assert_eq!(actual, "import os\nimport sys\n\nx = 1\n");
+    }
+
+    #[cfg(feature = "lint")]
+    #[test]
+    fn check_reports_default_rules() {
+        let diagnostics = check("import os\n", &Config::default()).unwrap();
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, "F401");
+        assert_eq!((diagnostics[0].start.line, diagnostics[0].start.column), (1, 8));
+        assert_eq!((diagnostics[0].end.line, diagnostics[0].end.column), (1, 10));
+        assert!(diagnostics[0].fixable);
+    }
+
+    #[cfg(feature = "lint")]
+    #[test]
+    fn check_honors_select_and_ignore() {
+        let input = "import os\nx == None\n";
+
+        let config = Config::default().with_select(vec!["E".into(), "F".into()]);
+        let codes: Vec<_> =
+            check(input, &config).unwrap().into_iter().map(|diagnostic| diagnostic.code).collect();
+        assert_eq!(codes, ["F401", "E711"]);
+
+        let config = config.with_ignore(vec!["F401".into()]);
+        let codes: Vec<_> =
+            check(input, &config).unwrap().into_iter().map(|diagnostic| diagnostic.code).collect();
+        assert_eq!(codes, ["E711"]);
+    }
+
+    #[test]
+    fn is_formatted_compares_against_formatter_output() {
+        assert!(is_formatted_with_config("x = 1\n", Config::default()).unwrap());
+        assert!(!is_formatted_with_config("x=1\n", Config::default()).unwrap());
+        assert!(!is_formatted_with_config("x = 1", Config::default()).unwrap());
+    }
+
+    #[test]
+    fn format_diff_renders_unified_diff() {
+        let config = Config::default().with_path("main.py".into());
+
+        let actual = format_diff_with_config("x = 1\ny=2\n", config).unwrap();
+
+        assert_eq!(actual, "--- main.py\n+++ main.py\n@@ -1,2 +1,2 @@\n x = 1\n-y=2\n+y = 2\n");
+    }
+
+    #[test]
+    fn format_diff_is_empty_without_changes() {
+        assert_eq!(format_diff_with_config("x = 1\n", Config::default()).unwrap(), "");
+    }
+
+    #[test]
+    fn format_cell_keeps_magics_verbatim() {
+        let input = "%matplotlib  inline\n!pip install foo\nx=1\ny = %timeit  f( 1 )\n";
+
+        let actual = format_cell(input, Config::default()).unwrap();
+
+        assert_eq!(actual, "%matplotlib  inline\n!pip install foo\nx = 1\ny = %timeit  f( 1 )\n");
+    }
+
+    #[test]
+    fn format_cell_keeps_leading_cell_magic() {
+        let actual = format_cell("%%time\nx=[1,2]", Config::default()).unwrap();
+        assert_eq!(actual, "%%time\nx = [1, 2]");
+
+        let actual = format_cell("%%bash\necho  hi", Config::default()).unwrap();
+        assert_eq!(actual, "%%bash\necho  hi");
+    }
+
+    #[test]
+    fn format_cell_trims_an_added_crlf() {
+        let config = Config::default().with_line_ending(LineEnding::CrLf);
+        assert_eq!(format_cell("x=1", config).unwrap(), "x = 1");
+
+        let config = Config::default().with_line_ending(LineEnding::Auto);
+        assert_eq!(format_cell("x=1\r\ny=2", config.clone()).unwrap(), "x = 1\r\ny = 2");
+        assert_eq!(format_cell("x=1\r\n", config).unwrap(), "x = 1\r\n");
+    }
+
+    #[test]
+    fn docstring_code_format_is_opt_in() {
+        let input = "def f():\n    \"\"\"\n    >>> x=[1,2]\n\n    ```python\n    y=3\n    ```\n    \"\"\"\n";
+
+        let actual = format_with_config(input, Config::default()).unwrap();
+        assert_eq!(actual, input);
+
+        let config = Config::default().with_docstring_code_format(true);
+        let actual = format_with_config(input, config).unwrap();
+        assert_eq!(
+            actual,
+            "def f():\n    \"\"\"\n    >>> x = [1, 2]\n\n    ```python\n    y = 3\n    ```\n    \"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn preview_maps_to_preview_mode() {
+        assert!(!PyFormatOptions::from(Config::default()).preview().is_enabled());
+        assert!(PyFormatOptions::from(Config::default().with_preview(true)).preview().is_enabled());
+        assert!(!PyFormatOptions::from(Config::default().with_preview(false))
+            .preview()
+            .is_enabled());
+    }
+
+    #[test]
+    fn skip_magic_trailing_comma_aliases_ignore() {
+        let magic_trailing_comma =
+            |config: Config| PyFormatOptions::from(config).magic_trailing_comma();
+
+        assert_eq!(
+            magic_trailing_comma(Config::default().with_skip_magic_trailing_comma(true)),
+            MagicTrailingComma::Ignore
+        );
+        assert_eq!(
+            magic_trailing_comma(Config::default().with_skip_magic_trailing_comma(false)),
+            MagicTrailingComma::Respect
+        );
+        assert_eq!(
+            magic_trailing_comma(
+                Config::default()
+                    .with_skip_magic_trailing_comma(true)
+                    .with_magic_trailing_comma(MagicTrailingComma::Respect)
+            ),
+            MagicTrailingComma::Respect
+        );
+
+        let config = Config::default().with_skip_magic_trailing_comma(true);
+        assert_eq!(format_with_config("x = [\n    1,\n]\n", config).unwrap(), "x = [1]\n");
+    }
+
+    #[test]
+    fn quote_style_preserve_keeps_quotes() {
+        let input = "a = 'single'\nb = \"double\"\nc = 'it\\'s'\n";
+        let config = Config::default().with_quote_style(QuoteStyle::Preserve);
+
+        assert_eq!(format_with_config(input, config).unwrap(), input);
+
+        let config = Config::default().with_quote_style(QuoteStyle::Double);
+        assert_eq!(
+            format_with_config(input, config).unwrap(),
+            "a = \"single\"\nb = \"double\"\nc = \"it's\"\n"
+        );
+    }
+
+    #[test]
+    fn config_from_pyproject_reads_ruff_tables() {
+        let toml = r#"
+[project]
+name = "demo"
+
+[tool.ruff]
+line-length = 120
+target-version = "py311"
+unknown-key = true
+
+[tool.ruff.format]
+quote-style = "single"
+skip-magic-trailing-comma = true
+
+[tool.ruff.lint]
+select = ["E", "F"]
+"#;
+
+        let config = Config::from_pyproject(toml).unwrap();
+        assert_eq!(config.select, Some(vec!["E".to_string(), "F".to_string()]));
+
+        let options = PyFormatOptions::from(config);
+        assert_eq!(options.line_width().value(), 120);
+        assert_eq!(options.quote_style(), QuoteStyle::Single);
+        assert_eq!(options.magic_trailing_comma(), MagicTrailingComma::Ignore);
+    }
+
+    #[test]
+    fn config_from_pyproject_defaults_without_ruff_tables() {
+        let config = Config::from_pyproject("[project]\nname = \"demo\"\n").unwrap();
+        let options = PyFormatOptions::from(config);
+
+        assert_eq!(options.line_width().value(), 88);
+        assert_eq!(options.quote_style(), QuoteStyle::Double);
+    }
+
+    #[test]
+    fn pyi_path_selects_stub_formatting() {
+        let config = Config::default().with_path("module.pyi".into());
+        assert!(PyFormatOptions::from(config.clone()).source_type().is_stub());
+
+        let input = "def f() -> int: ...\ndef g() -> str: ...\n";
+        assert_eq!(format_with_config(input, config).unwrap(), input);
+    }
+
+    #[test]
+    fn format_many_isolates_failures() {
+        let inputs = vec![
+            SourceFile { path: "a.py".into(), content: "x=1\n".into() },
+            SourceFile { path: "b.py".into(), content: "x = (\n".into() },
+            SourceFile { path: "c.pyi".into(), content: "def f() -> int:\n    ...\n".into() },
+        ];
+
+        let actual = format_many_with_config(inputs, Config::default());
+
+        assert_eq!(
+            actual[0],
+            FormattedFile { path: "a.py".into(), code: Some("x = 1\n".into()), error: None }
+        );
+        assert_eq!(actual[1].path, "b.py");
+        assert!(actual[1].code.is_none() && actual[1].error.is_some());
+        assert_eq!(
+            actual[2],
+            FormattedFile {
+                path: "c.pyi".into(),
+                code: Some("def f() -> int: ...\n".into()),
+                error: None
+            }
+        );
+    }
+
+    #[test]
+    fn format_best_effort_formats_valid_prefix() {
+        let input = "x=1\ny  =  2\nz = (\n";
+
+        let actual = format_best_effort(input, Config::default()).unwrap();
+
+        assert_eq!(actual.code, "x = 1\ny = 2\nz = (\n");
+        assert_eq!(actual.skipped, [ByteRange { start: 11, end: 18 }]);
+    }
+
+    #[test]
+    fn format_best_effort_keeps_input_when_first_statement_is_invalid() {
+        let input = "x = (\ny  =  2\n";
+
+        let actual = format_best_effort(input, Config::default()).unwrap();
+
+        assert_eq!(actual.code, input);
+        assert_eq!(actual.skipped, [ByteRange { start: 0, end: 14 }]);
+    }
+
+    #[test]
+    fn format_best_effort_formats_valid_input_fully() {
+        let actual = format_best_effort("x=1\n", Config::default()).unwrap();
+
+        assert_eq!(actual.code, "x = 1\n");
+        assert!(actual.skipped.is_empty());
+    }
+
+    #[test]
+    fn format_best_effort_keeps_the_line_ending_of_the_input() {
+        let input = "x=1\r\ny  =  2\r\nz = (\r\n";
+        let config = Config::default().with_line_ending(LineEnding::Auto);
+
+        let actual = format_best_effort(input, config).unwrap();
+
+        assert_eq!(actual.code, "x = 1\r\ny = 2\r\nz = (\r\n");
+        assert!(!actual.code.replace("\r\n", "").contains('\n'));
+    }
+
+    #[test]
+    fn format_with_status_reports_changes() {
+        assert_eq!(
+            format_with_status_with_config("x=1\n", Config::default()).unwrap(),
+            FormatStatusOutput { code: "x = 1\n".into(), changed: true }
+        );
+        assert_eq!(
+            format_with_status_with_config("x = 1\n", Config::default()).unwrap(),
+            FormatStatusOutput { code: "x = 1\n".into(), changed: false }
+        );
+    }
+
+    #[test]
+    fn insert_final_newline_false_keeps_missing_newline() {
+        let config = Config::default().with_insert_final_newline(false);
+
+        assert_eq!(format_with_config("x=1", config.clone()).unwrap(), "x = 1");
+        assert_eq!(format_with_config("x=1\n", config.clone()).unwrap(), "x = 1\n");
+        assert_eq!(format_with_config("x=1\n\n\n", config).unwrap(), "x = 1\n");
+    }
+
+    #[test]
+    fn insert_final_newline_true_adds_exactly_one() {
+        let config = Config::default().with_insert_final_newline(true);
+
+        assert_eq!(format_with_config("x=1", config.clone()).unwrap(), "x = 1\n");
+        assert_eq!(format_with_config("x=1\n\n\n", config).unwrap(), "x = 1\n");
+    }
+
+    #[test]
+    fn auto_line_ending_follows_input() {
+        let config = Config::default().with_line_ending(LineEnding::Auto);
+
+        assert_eq!(
+            format_with_config("x=1\r\ny=2\r\n", config.clone()).unwrap(),
+            "x = 1\r\ny = 2\r\n"
+        );
+        assert_eq!(format_with_config("x=1\ny=2\n", config.clone()).unwrap(), "x = 1\ny = 2\n");
+        // mixed endings fall back to LF
+        assert_eq!(format_with_config("x=1\r\ny=2\n", config).unwrap(), "x = 1\ny = 2\n");
+    }
+
+    #[test]
+    fn bom_is_preserved() {
+        assert_eq!(
+            format_with_config("\u{feff}x=1", Config::default()).unwrap(),
+            "\u{feff}x = 1\n"
+        );
+        assert_eq!(
+            format_result_with_config("\u{feff}x=1\n", Config::default()).code.as_deref(),
+            Some("\u{feff}x = 1\n")
+        );
+        assert_eq!(format_with_config("x=1\n", Config::default()).unwrap(), "x = 1\n");
+    }
+
+    #[test]
+    fn validate_config_reports_each_invalid_field() {
+        assert_eq!(Config::validate(&json!({ "indent_width": 4, "line_width": 100 })), Ok(()));
+        assert_eq!(Config::validate(&json!({ "docstring_code_line_width": "dynamic" })), Ok(()));
+
+        let error = Config::validate(&json!({ "indent_width": 0, "lineWidth": -1 })).unwrap_err();
+        assert_eq!(
+            error,
+            "`indent_width` must be an integer between 1 and 24, got 0\n\
+             `line_width` must be an integer between 1 and 320, got -1"
+        );
+
+        assert!(Config::validate(&json!({ "quote_style": "backtick" })).is_err());
+
+        let error = Config::validate(&json!({
+            "indent_width": 0,
+            "quote_style": "backtick",
+            "targetVersion": "py2",
+            "preview": true,
+        }))
+        .unwrap_err();
+        let lines: Vec<_> = error.lines().collect();
+        assert_eq!(lines.len(), 3, "{error}");
+        assert_eq!(lines[0], "`indent_width` must be an integer between 1 and 24, got 0");
+        assert!(lines[1].starts_with("`quote_style`: unknown variant `backtick`"), "{error}");
+        assert!(lines[2].starts_with("`targetVersion`: unknown variant `py2`"), "{error}");
+        assert!(Config::validate(&json!({ "tab_width": 17 })).is_err());
+        assert!(Config::validate(&json!({ "line_width": 80.5 })).is_err());
+    }
+
+    #[test]
+    fn default_config_spells_out_ruff_defaults() {
+        let defaults = serde_json::to_value(Config::defaults()).unwrap();
+
+        assert_eq!(defaults["indent_style"], "space");
+        assert_eq!(defaults["indent_width"], 4);
+        assert_eq!(defaults["tab_width"], 8);
+        assert_eq!(defaults["line_width"], 88);
+        assert_eq!(defaults["line_ending"], "lf");
+        assert_eq!(defaults["quote_style"], "double");
+        assert_eq!(defaults["magic_trailing_comma"], serde_json::Value::Null);
+        assert_eq!(defaults["skip_magic_trailing_comma"], false);
+        assert_eq!(defaults["insert_final_newline"], true);
+        assert_eq!(Config::validate(&defaults), Ok(()));
+
+        // A form seeded with the defaults can still turn the checkbox on, without a warning.
+        let config = Config::defaults().with_skip_magic_trailing_comma(true);
+        assert!(config.warnings().is_empty());
+        assert_eq!(
+            PyFormatOptions::from(config).magic_trailing_comma(),
+            MagicTrailingComma::Ignore
+        );
+    }
+
+    #[test]
+    fn version_names_the_ruff_build() {
+        let version = crate::version();
+
+        assert!(!version.is_empty());
+        assert!(!version.contains(char::is_whitespace), "{version}");
+    }
+
+    #[test]
+    fn format_with_cursor_follows_unchanged_characters() {
+        let input = "x=1\ny  =  2\n";
+
+        let actual = crate::format_with_cursor_with_config(input, 4, Config::default()).unwrap();
+        assert_eq!(actual.code, "x = 1\ny = 2\n");
+        assert_eq!(actual.cursor, 6);
+
+        let actual = crate::format_with_cursor_with_config(input, 10, Config::default()).unwrap();
+        assert_eq!(actual.cursor, 10);
+    }
+
+    #[test]
+    fn map_cursor_handles_collapsed_lines() {
+        let old = "f(\n    a,\n    b\n)\n";
+        let new = "f(a, b)\n";
+
+        // on `b`
+        assert_eq!(map_cursor(old, new, 14).unwrap(), 5);
+        // inside the indentation that disappeared
+        assert!((4..=5).contains(&map_cursor(old, new, 12).unwrap()));
+        // at the very end
+        assert_eq!(map_cursor(old, new, 18).unwrap(), 8);
+    }
+
+    #[test]
+    fn map_cursor_counts_utf16() {
+        let old = "s='\u{1F600}'\nx=1\n";
+        let new = "s = \"\u{1F600}\"\nx = 1\n";
+
+        // on `x`, after the surrogate pair
+        assert_eq!(map_cursor(old, new, 7).unwrap(), 9);
+    }
+
+    #[test]
+    fn map_cursor_keeps_column_on_unchanged_lines() {
+        let old = "a=1\nb = 2\nc=3\n";
+        let new = "a = 1\nb = 2\nc = 3\n";
+
+        // on `2`, between two reformatted lines
+        assert_eq!(map_cursor(old, new, 8).unwrap(), 10);
+        // on `3`
+        assert_eq!(map_cursor(old, new, 12).unwrap(), 16);
+    }
+
+    #[test]
+    fn dump_tokens_lists_ranges() {
+        assert_eq!(
+            dump_tokens("x = 1\n"),
+            "Name 0..1 \"x\"\nEqual 2..3 \"=\"\nInt 4..5 \"1\"\nNewline 5..6 \"\\n\"\n"
+        );
+        assert!(dump_tokens("x = (\n").contains("\nerror "));
+    }
+
+    #[test]
+    fn syntax_errors_reports_all_recoverable_errors() {
+        let errors = syntax_errors("x = = 1\ny = 2\nz = = 3\n", Config::default());
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].start, Location { line: 1, column: 5 });
+        assert_eq!(errors[1].start, Location { line: 3, column: 5 });
+
+        assert!(syntax_errors("x = 1\n", Config::default()).is_empty());
+    }
+
+    #[test]
+    fn format_with_skips_keeps_fmt_off_regions() {
+        let input = "# fmt: off\nx  =  [1,2]\n# fmt: on\ny=2\n";
+
+        let actual = crate::format_with_skips_with_config(input, Config::default()).unwrap();
+
+        assert_eq!(actual.code, "# fmt: off\nx  =  [1,2]\n# fmt: on\ny = 2\n");
+        assert_eq!(actual.skipped_ranges, [ByteRange { start: 0, end: 32 }]);
+    }
+
+    #[test]
+    fn format_with_skips_keeps_fmt_skip_lines() {
+        let input = "a  =  1  # fmt: skip\nb=2\n";
+
+        let actual = crate::format_with_skips_with_config(input, Config::default()).unwrap();
+
+        assert_eq!(actual.code, "a  =  1  # fmt: skip\nb = 2\n");
+        assert_eq!(actual.skipped_ranges, [ByteRange { start: 0, end: 20 }]);
+
+        let input = "x = [\n    1,2,\n]  # fmt: skip\ny=2\n";
+        let actual = crate::format_with_skips_with_config(input, Config::default()).unwrap();
+        assert_eq!(actual.code, "x = [\n    1,2,\n]  # fmt: skip\ny = 2\n");
+        assert_eq!(actual.skipped_ranges, [ByteRange { start: 0, end: 29 }]);
+
+        let input = "# fmt: skip\nx=1\n";
+        let actual = crate::format_with_skips_with_config(input, Config::default()).unwrap();
+        assert_eq!(actual.code, "# fmt: skip\nx = 1\n");
+        assert!(actual.skipped_ranges.is_empty());
+    }
+
+    #[test]
+    fn format_with_skips_runs_unterminated_fmt_off_to_eof() {
+        let input = "x=1\n# fmt: off\ny  =  2\nz  =  3\n";
+
+        let actual = crate::format_with_skips_with_config(input, Config::default()).unwrap();
+
+        assert_eq!(actual.code, "x = 1\n# fmt: off\ny  =  2\nz  =  3\n");
+        assert_eq!(actual.skipped_ranges, [ByteRange { start: 4, end: 31 }]);
+    }
+
+    #[test]
+    fn format_with_skips_ends_unterminated_fmt_off_with_its_block() {
+        let input = "def f():\n    # fmt: off\n    x  =  1\ny=2\n";
+
+        let actual = crate::format_with_skips_with_config(input, Config::default()).unwrap();
+
+        assert!(
+            actual.code.starts_with("def f():\n    # fmt: off\n    x  =  1\n"),
+            "{}",
+            actual.code
+        );
+        assert!(actual.code.ends_with("\ny = 2\n"), "{}", actual.code);
+        assert_eq!(actual.skipped_ranges, [ByteRange { start: 9, end: 36 }]);
+    }
+
+    #[test]
+    fn format_with_skips_ignores_trailing_fmt_off() {
+        let input = "x = [1,2]  # fmt: off\ny=2\n";
+
+        let actual = crate::format_with_skips_with_config(input, Config::default()).unwrap();
+
+        assert_eq!(actual.code, "x = [1, 2]  # fmt: off\ny = 2\n");
+        assert!(actual.skipped_ranges.is_empty());
+    }
+
+    #[test]
+    fn blank_lines_top_level_sets_separation() {
+        let input = "def f():\n    pass\ndef g():\n    pass\nx = 1\n";
+
+        let config = Config::default().with_blank_lines_top_level(1);
+        assert_eq!(
+            format_with_config(input, config).unwrap(),
+            "def f():\n    pass\n\ndef g():\n    pass\n\nx = 1\n"
+        );
+
+        let config = Config::default().with_blank_lines_top_level(2);
+        assert_eq!(
+            format_with_config(input, config).unwrap(),
+            "def f():\n    pass\n\n\ndef g():\n    pass\n\n\nx = 1\n"
+        );
+    }
+
+    #[test]
+    fn blank_lines_top_level_keeps_trailing_comments_in_their_body() {
+        let config = Config::default().with_blank_lines_top_level(1);
+
+        assert_eq!(
+            format_with_config(
+                "def f():\n    pass\n    # trailing\ndef g(): ...\n",
+                config.clone()
+            )
+            .unwrap(),
+            "def f():\n    pass\n    # trailing\n\ndef g(): ...\n"
+        );
+        assert_eq!(
+            format_with_config("x = 1\n# about g\n@decorator\ndef g(): ...\n", config).unwrap(),
+            "x = 1\n\n# about g\n@decorator\ndef g(): ...\n"
+        );
+    }
+
+    #[test]
+    fn blank_lines_top_level_keeps_fmt_off_regions() {
+        let input = "# fmt: off\ndef f(): pass\ndef g(): pass\n# fmt: on\n";
+        let config = Config::default().with_blank_lines_top_level(1);
+
+        assert_eq!(format_with_config(input, config).unwrap(), input);
+    }
+
+    #[cfg(feature = "lint")]
+    #[test]
+    fn organize_imports_ignores_blank_lines_after_imports() {
+        let input = "import sys\nimport os\nx = 1\n";
+        let config = Config::default().with_blank_lines_after_imports(2);
+
+        assert_eq!(
+            organize_imports(input, &config).unwrap(),
+            organize_imports(input, &Config::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn blank_lines_after_imports_sets_separation() {
+        let config = Config::default().with_blank_lines_after_imports(2);
+
+        assert_eq!(
+            format_with_config("import os\nimport sys\nx = 1\n", config).unwrap(),
+            "import os\nimport sys\n\n\nx = 1\n"
+        );
+    }
+
+    #[test]
+    fn blank_lines_out_of_range_are_clamped_with_warning() {
+        let config =
+            Config::default().with_blank_lines_top_level(5).with_blank_lines_after_imports(0);
+
+        assert_eq!(config.blank_lines_top_level(), Some(2));
+        assert_eq!(config.blank_lines_after_imports(), Some(1));
+        assert_eq!(
+            config.warnings(),
+            [
+                "`blank_lines_top_level` must be between 1 and 2, using 2 instead of 5",
+                "`blank_lines_after_imports` must be between 1 and 2, using 1 instead of 0",
+            ]
+        );
+        assert!(Config::default().with_blank_lines_top_level(1).warnings().is_empty());
+    }
+
+    #[test]
+    fn normalize_only_keeps_layout() {
+        let input = "x  =  [1,2]   \r\ny = 'a'\t\n\n\n";
+
+        assert_eq!(normalize(input, &Config::default()), "x  =  [1,2]\ny = 'a'\n");
+
+        let config = Config::default().with_line_ending(LineEnding::CrLf);
+        assert_eq!(normalize(input, &config), "x  =  [1,2]\r\ny = 'a'\r\n");
+
+        let config = Config::default().with_insert_final_newline(false);
+        assert_eq!(normalize("x = 1  ", &config), "x = 1");
+        assert_eq!(normalize("x = 1  ", &Config::default()), "x = 1\n");
+    }
+
+    #[test]
+    fn normalize_only_keeps_whitespace_inside_strings() {
+        let input = "s = \"\"\"line   \nend\"\"\"   \n";
+
+        assert_eq!(normalize(input, &Config::default()), "s = \"\"\"line   \nend\"\"\"\n");
+    }
+
+    #[test]
+    fn format_fragment_formats_bare_expressions() {
+        assert_eq!(format_fragment("foo( a ,b )", Config::default()).unwrap(), "foo(a, b)");
+    }
+
+    #[test]
+    fn format_fragment_keeps_indentation() {
+        let input = "        x=1\n        if x :\n            y=[1,2]\n";
+
+        assert_eq!(
+            format_fragment(input, Config::default()).unwrap(),
+            "        x = 1\n        if x:\n            y = [1, 2]\n"
+        );
+    }
+
+    #[test]
+    fn format_fragment_rejects_incomplete_blocks() {
+        let error = format_fragment("if x :", Config::default()).unwrap_err();
+
+        assert_eq!(error, "the fragment ends with an incomplete block: `if x :` has no body");
+    }
+
+    #[test]
+    fn detect_min_version_finds_version_gated_syntax() {
+        let cases = [
+            ("x = 1\nwith (open(a)) as f:\n    pass\n", TargetVersion::Py37),
+            ("if (n := 10) > 5:\n    pass\n", TargetVersion::Py38),
+            ("def f(a, /, b):\n    pass\n", TargetVersion::Py38),
+            ("print(f\"{x=}\")\n", TargetVersion::Py38),
+            ("@buttons[0].clicked.connect\ndef f():\n    pass\n", TargetVersion::Py39),
+            ("match x:\n    case 1:\n        pass\n", TargetVersion::Py310),
+            ("with (open(a) as f, open(b) as g):\n    pass\n", TargetVersion::Py310),
+            ("try:\n    pass\nexcept* ValueError:\n    pass\n", TargetVersion::Py311),
+            ("x = a[(*b,)]\n", TargetVersion::Py37),
+            ("x = a[*b]\n", TargetVersion::Py311),
+            ("x = a[int, *b]\n", TargetVersion::Py311),
+            ("def f(*args: *Ts):\n    pass\n", TargetVersion::Py311),
+            ("def f[T](x: T) -> T:\n    return x\n", TargetVersion::Py312),
+            ("type Point = tuple[float, float]\n", TargetVersion::Py312),
+            ("x = f\"{'a'}\" + f'''{\"a\"}'''\n", TargetVersion::Py37),
+            ("x = f\"{\"a\"}\"\n", TargetVersion::Py312),
+            ("x = f\"{'\\n'.join(a)}\"\n", TargetVersion::Py312),
+            ("x = f\"{y:{\"a\"}}\"\n", TargetVersion::Py312),
+            ("x = f\"{(\n    a\n)}\"\n", TargetVersion::Py312),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(detect_min_version(input), Ok(expected), "{input}");
+        }
+
+        assert!(detect_min_version("x = (\n").is_err());
+    }
+
+    #[test]
+    fn tab_width_decides_where_tab_indented_lines_wrap() {
+        let input = "def f():\n\treturn aaaa + bbbb + cccc\n";
+        let config = |tab_width| {
+            Config::default()
+                .with_indent_style(IndentStyle::Tab)
+                .with_line_width(NonZeroU16::new(30).unwrap())
+                .with_tab_width(NonZeroU8::new(tab_width).unwrap())
+        };
+
+        assert_eq!(format_with_config(input, config(4)).unwrap(), input);
+
+        let wrapped = format_with_config(input, config(8)).unwrap();
+        assert!(wrapped.starts_with("def f():\n\treturn (\n"), "{wrapped}");
+    }
+
+    #[test]
+    fn format_stats_counts_changed_lines_and_bytes() {
+        let input = "x=1\ny = 2\nz=[1,2]\n";
+
+        assert_eq!(
+            format_stats_with_config(input, Config::default()).unwrap(),
+            FormatStatsOutput {
+                code: "x = 1\ny = 2\nz = [1, 2]\n".to_string(),
+                lines_changed: 2,
+                bytes_before: 18,
+                bytes_after: 23,
+            }
+        );
+
+        let unchanged = format_stats_with_config("x = 1\n", Config::default()).unwrap();
+        assert_eq!(unchanged.lines_changed, 0);
+        assert_eq!(unchanged.bytes_before, unchanged.bytes_after);
+    }
+
+    #[test]
+    fn formatter_reuses_its_config_across_calls() {
+        let formatter =
+            Formatter::from_config(Config::default().with_quote_style(QuoteStyle::Single));
+
+        assert_eq!(formatter.format("x = \"a\"\n", None).unwrap(), "x = 'a'\n");
+        assert_eq!(formatter.format("y = \"b\"\n", None).unwrap(), "y = 'b'\n");
+        assert_eq!(
+            formatter.format("z = \"c\"\n", Some("stub.pyi".to_string())).unwrap(),
+            "z = 'c'\n"
+        );
+        assert!(formatter.format("x = (\n", None).is_err());
+    }
+
+    #[test]
+    fn format_errors_include_a_code_frame() {
+        let error = format_with_config("x = 1\ny = )\n", Config::default()).unwrap_err();
+        let (message, frame) = error.split_once('\n').unwrap();
+
+        assert!(message.ends_with(" at 2:5"), "{message}");
+        assert_eq!(frame, "  |\n2 | y = )\n  |     ^");
+    }
+
+    #[test]
+    fn docstring_quote_style_is_independent_of_quote_style() {
+        let input = "def f():\n    \"\"\"Don't.\"\"\"\n    return \"a\"\n";
+        let config = Config::default()
+            .with_quote_style(QuoteStyle::Double)
+            .with_docstring_quote_style(QuoteStyle::Single);
+
+        assert_eq!(
+            format_with_config(input, config).unwrap(),
+            "def f():\n    '''Don't.'''\n    return \"a\"\n"
+        );
+
+        let input = "x = 'a'\ny = '''b'''\nz = \"\"\"c\"\"\"\n";
+        let config = Config::default()
+            .with_quote_style(QuoteStyle::Double)
+            .with_docstring_quote_style(QuoteStyle::Preserve);
+
+        assert_eq!(
+            format_with_config(input, config).unwrap(),
+            "x = \"a\"\ny = '''b'''\nz = \"\"\"c\"\"\"\n"
+        );
+
+        // Ending with the new quote would close the string early.
+        let input = "x = \"\"\"it's '\"\"\"\n";
+        let config = Config::default().with_docstring_quote_style(QuoteStyle::Single);
+        assert_eq!(format_with_config(input, config).unwrap(), input);
+    }
+
+    #[test]
+    fn docstring_quote_style_applies_to_range_formatting() {
+        let config = Config::default().with_docstring_quote_style(QuoteStyle::Single);
+        let input = "x = \"\"\"a\"\"\"\ny  =  \"\"\"b\"\"\"\n";
+        let range = TextRange::new(TextSize::new(12), TextSize::new(13));
+
+        assert_eq!(
+            format_range_with_config(input, range, config).unwrap(),
+            "x = \"\"\"a\"\"\"\ny = '''b'''\n"
+        );
+    }
+
+    #[test]
+    fn docstring_quote_style_keeps_suppressed_strings() {
+        let config = Config::default().with_docstring_quote_style(QuoteStyle::Single);
+
+        let input = "# fmt: off\nx = \"\"\"a\"\"\"\n# fmt: on\n";
+        assert_eq!(format_with_config(input, config.clone()).unwrap(), input);
+
+        let input = "x = \"\"\"a\"\"\"  # fmt: skip\ny = \"\"\"b\"\"\"\n";
+        assert_eq!(
+            format_with_config(input, config).unwrap(),
+            "x = \"\"\"a\"\"\"  # fmt: skip\ny = '''b'''\n"
+        );
+    }
+
+    #[test]
+    fn config_schema_describes_every_field_with_defaults() {
+        let schema = Config::schema();
+        let properties = schema["properties"].as_object().unwrap();
+
+        assert_eq!(schema["$schema"], "https://json-schema.org/draft/2020-12/schema");
+        assert!(properties.contains_key("docstring_quote_style"));
+        assert!(!properties.contains_key("path"));
+        assert_eq!(properties["line_width"]["default"], 88);
+        assert_eq!(properties["insert_final_newline"]["default"], true);
+        assert!(properties["select"].get("default").is_none());
+
+        for (field, range) in [
+            ("indent_width", INDENT_WIDTH_RANGE),
+            ("tab_width", TAB_WIDTH_RANGE),
+            ("line_width", LINE_WIDTH_RANGE),
+        ] {
+            let bound = |key: &str| properties[field][key].as_f64();
+            assert_eq!(bound("minimum"), Some(f64::from(*range.start())), "{field}");
+            assert_eq!(bound("maximum"), Some(f64::from(*range.end())), "{field}");
+        }
+    }
+
+    #[test]
+    fn format_verbose_reports_warnings_and_still_formats() {
+        let config = Config::default()
+            .with_blank_lines_top_level(5)
+            .with_preview(true)
+            .with_magic_trailing_comma(MagicTrailingComma::Respect)
+            .with_skip_magic_trailing_comma(true);
+
+        let mut warnings = vec![];
+        let code = format_verbose_with_config("x=1\n", config, |warning| {
+            warnings.push(warning.to_string())
+        });
+
+        assert_eq!(code.unwrap(), "x = 1\n");
+        assert_eq!(
+            warnings,
+            [
+                "`blank_lines_top_level` must be between 1 and 2, using 2 instead of 5",
+                "`skip_magic_trailing_comma` is ignored because `magic_trailing_comma` is set",
+                "`preview` is enabled, its unstable formatting may change with any ruff upgrade",
+            ]
+        );
+
+        // Two spellings of the same setting don't conflict.
+        let config = Config::default()
+            .with_magic_trailing_comma(MagicTrailingComma::Ignore)
+            .with_skip_magic_trailing_comma(true);
+        assert!(config.warnings().is_empty());
+    }
+
+    #[test]
+    fn format_incremental_only_touches_dirty_lines() {
+        let input = "x=1\ny=2\nz=3\nw=4\n";
+        let ranges = |ranges: &[(u32, u32)]| {
+            ranges.iter().map(|&(start, end)| LineRange { start, end }).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            format_incremental(input, ranges(&[(4, 4), (2, 2)]), Config::default()).unwrap(),
+            "x=1\ny = 2\nz=3\nw = 4\n"
+        );
+        assert_eq!(
+            format_incremental(input, ranges(&[(1, 2), (2, 3)]), Config::default()).unwrap(),
+            "x = 1\ny = 2\nz = 3\nw=4\n"
+        );
+        assert_eq!(
+            format_incremental(input, ranges(&[(3, 99)]), Config::default()).unwrap(),
+            "x=1\ny=2\nz = 3\nw = 4\n"
+        );
+        assert!(format_incremental(input, ranges(&[(3, 2)]), Config::default()).is_err());
+    }
+
+    #[test]
+    fn leading_ruff_comment_overrides_line_width() {
+        let call = "foo(aaaaaaaaaaaaaaaaaaaa, bbbbbbbbbbbbbbbbbbbb, cccccccccccccccccccc, dddddddddddddddddddd)\n";
+
+        let input = format!("#!/usr/bin/env python\n# ruff: line-length = 120\n{call}");
+        assert_eq!(format_with_config(&input, Config::default()).unwrap(), input);
+
+        let input = format!("# ruff: line-length = 120\nx = 1\n# ruff: line-length = 40\n{call}");
+        assert_eq!(format_with_config(&input, Config::default()).unwrap(), input);
+
+        let input = format!("\u{feff}# ruff: line-length = 120\n{call}");
+        assert_eq!(format_with_config(&input, Config::default()).unwrap(), input);
+
+        for marker in ["# ruff: line-width = 120", "# ruff: line-length = 999", "# ruff: noqa"] {
+            let input = format!("{marker}\n{call}");
+            assert_ne!(format_with_config(&input, Config::default()).unwrap(), input, "{marker}");
+        }
+    }
+
+    #[cfg(feature = "lint")]
+    #[test]
+    fn is_fixable_looks_up_rule_metadata() {
+        assert!(is_fixable("F401"));
+        assert!(is_fixable("I001"));
+        assert!(!is_fixable("E999"));
+        assert!(!is_fixable("F821"));
+        assert!(!is_fixable("NOPE1"));
+    }
+
+    #[cfg(feature = "lint")]
+    #[test]
+    fn fix_applies_selected_rules_and_counts_them() {
+        let config = Config::default().with_select(vec!["F401".to_string()]);
+        let output = fix("import os\nimport sys\nx = 1\n", &config).unwrap();
+
+        assert_eq!(output.code, "x = 1\n");
+        assert_eq!(output.applied, [AppliedFix { code: "F401".to_string(), count: 2 }]);
+
+        let input = "def f():\n    x = 1\n";
+        let config = Config::default().with_select(vec!["F841".to_string()]);
+
+        let output = fix(input, &config).unwrap();
+        assert_eq!(output.code, input);
+        assert!(output.applied.is_empty());
+
+        let output = fix(input, &config.with_unsafe_fixes(true)).unwrap();
+        assert_eq!(output.code, "def f():\n    pass\n");
+    }
+
+    #[test]
+    fn crlf_input_is_formatted_exactly() {
+        let input = "x = 'a'\r\nif x :\r\n    y = 'b'\r\n";
+        let crlf = "x = \"a\"\r\nif x:\r\n    y = \"b\"\r\n";
+
+        let config = Config::default().with_line_ending(LineEnding::Auto);
+        assert_eq!(format_with_config(input, config.clone()).unwrap(), crlf);
+        assert_eq!(format_with_config(crlf, config).unwrap(), crlf);
+
+        let config = Config::default().with_quote_style(QuoteStyle::Single);
+        assert_eq!(
+            format_with_config(input, config.with_line_ending(LineEnding::CrLf)).unwrap(),
+            "x = 'a'\r\nif x:\r\n    y = 'b'\r\n"
+        );
+        assert_eq!(
+            format_with_config(input, Config::default()).unwrap(),
+            "x = \"a\"\nif x:\n    y = \"b\"\n"
+        );
+
+        let config: Config = serde_json::from_value(json!({ "line_ending": "crlf" })).unwrap();
+        assert_eq!(format_with_config("x=1\n", config).unwrap(), "x = 1\r\n");
+        assert!(serde_json::from_value::<Config>(json!({ "lineEnding": "cr_lf" })).is_ok());
+    }
+
+    #[test]
+    fn crlf_offsets_index_the_input_as_passed() {
+        let config = Config::default().with_line_ending(LineEnding::CrLf);
+        let actual = crate::format_with_cursor_with_config("x=1\r\ny=2\r\n", 5, config).unwrap();
+
+        assert_eq!(actual.code, "x = 1\r\ny = 2\r\n");
+        assert_eq!(&actual.code[actual.cursor as usize..], "y = 2\r\n");
+
+        let error = format_result_with_config("x = 1\r\ny = )\r\n", Config::default()).error;
+        let error = error.unwrap();
+        assert_eq!((error.line, error.column), (2, 5));
+    }
+
+    #[test]
+    fn check_formatted_lists_unclean_and_broken_files() {
+        let inputs = vec![
+            SourceFile { path: "clean.py".into(), content: "x = 1\n".into() },
+            SourceFile { path: "dirty.py".into(), content: "x=1\n".into() },
+            SourceFile { path: "broken.py".into(), content: "x = (\n".into() },
+        ];
+
+        let actual = check_formatted_with_config(inputs, Config::default());
+
+        assert_eq!(actual.would_reformat, ["dirty.py"]);
+        assert_eq!(actual.errored.len(), 1);
+        assert_eq!(actual.errored[0].path, "broken.py");
+    }
+
+    #[test]
+    fn source_type_overrides_the_path() {
+        let input = "def f() -> int:\n    ...\n";
+        let stub = "def f() -> int: ...\n";
+
+        let config = Config::default().with_source_type(SourceType::Stub);
+        assert_eq!(format_with_config(input, config.clone()).unwrap(), stub);
+        assert_eq!(format_with_config(input, config.with_path("a.py".into())).unwrap(), stub);
+
+        let config =
+            Config::default().with_source_type(SourceType::Python).with_path("a.pyi".into());
+        assert_eq!(format_with_config(input, config).unwrap(), input);
+
+        let config = Config::default().with_source_type(SourceType::Ipynb);
+        assert_eq!(format_with_config("%time x=1\n", config).unwrap(), "%time x=1\n");
+    }
+
+    #[test]
+    fn shebang_and_encoding_stay_on_the_first_lines() {
+        let header = "#!/usr/bin/env python3\n# -*- coding: utf-8 -*-\n";
+
+        assert_eq!(
+            format_with_config(&format!("{header}x=1\n"), Config::default()).unwrap(),
+            format!("{header}x = 1\n")
+        );
+        assert_eq!(
+            format_with_config(&format!("{header}\n\n\n\nx=1\n"), Config::default()).unwrap(),
+            format!("{header}\n\nx = 1\n")
+        );
+
+        let input = "\u{feff}#!/usr/bin/env python3\r\n# coding: latin-1\r\nx=1\r\n";
+        let config = Config::default().with_line_ending(LineEnding::Auto);
+        assert_eq!(
+            format_with_config(input, config).unwrap(),
+            "\u{feff}#!/usr/bin/env python3\r\n# coding: latin-1\r\nx = 1\r\n"
+        );
+    }
+
+    #[cfg(feature = "lint")]
+    #[test]
+    fn organize_imports_keeps_shebang_and_encoding_first() {
+        let header = "#!/usr/bin/env python3\n# -*- coding: utf-8 -*-\n";
+        let input = format!("{header}\"\"\"Doc.\"\"\"\nimport sys\nimport os\n");
+        let config = Config::default().with_organize_imports(true);
+
+        assert_eq!(
+            format_with_config(&input, config).unwrap(),
+            format!("{header}\"\"\"Doc.\"\"\"\n\nimport os\nimport sys\n")
+        );
+    }
+
+    #[cfg(not(feature = "lint"))]
+    #[test]
+    fn organize_imports_needs_the_lint_feature() {
+        let config = Config::default().with_organize_imports(true);
+
+        assert!(format_with_config("import sys\n", config).is_err());
+        assert_eq!(format_with_config("import sys\n", Config::default()).unwrap(), "import sys\n");
+    }
+
+    #[test]
+    fn normalize_numeric_literals_can_be_turned_off() {
+        let cases = [
+            ("0XFF", "0xFF"),
+            ("0xabcdef", "0xABCDEF"),
+            ("0B1010", "0b1010"),
+            ("0O777", "0o777"),
+            ("1E5", "1e5"),
+            ("1.5E-3", "1.5e-3"),
+            ("2J", "2j"),
+            ("1.5E-3J", "1.5e-3j"),
+            ("1_000", "1_000"),
+        ];
+
+        for (literal, normalized) in cases {
+            let input = format!("x=[{literal}]\n");
+
+            assert_eq!(
+                format_with_config(&input, Config::default()).unwrap(),
+                format!("x = [{normalized}]\n")
+            );
+
+            let config = Config::default().with_normalize_numeric_literals(false);
+            assert_eq!(format_with_config(&input, config).unwrap(), format!("x = [{literal}]\n"));
+        }
+    }
+
+    #[test]
+    fn normalize_numeric_literals_applies_to_partial_formatting() {
+        let config = Config::default().with_normalize_numeric_literals(false);
+        let input = "x=0XFF\ny=1E5\nz=2J\n";
+
+        let range = TextRange::new(TextSize::new(7), TextSize::new(12));
+        assert_eq!(
+            format_range_with_config(input, range, config.clone()).unwrap(),
+            "x=0XFF\ny = 1E5\nz=2J\n"
+        );
+
+        let dirty = vec![LineRange { start: 3, end: 3 }];
+        assert_eq!(
+            format_incremental(input, dirty, config.clone()).unwrap(),
+            "x=0XFF\ny=1E5\nz = 2J\n"
+        );
+
+        let actual = format_best_effort("x=0XFF\ny = (\n", config).unwrap();
+        assert_eq!(actual.code, "x = 0XFF\ny = (\n");
+    }
+
+    #[test]
+    fn format_edits_replace_only_changed_lines() {
+        let input = "x=1\ny = 2\nz=3\nw = [\n    1,2]\n";
+        let edits = format_edits_with_config(input, Config::default()).unwrap();
+
+        assert_eq!(
+            edits,
+            [
+                Edit { from: 0, to: 4, insert: "x = 1\n".to_string() },
+                Edit { from: 10, to: 29, insert: "z = 3\nw = [1, 2]\n".to_string() },
+            ]
+        );
+
+        let mut output = input.to_string();
+        for edit in edits.iter().rev() {
+            output.replace_range(edit.from as usize..edit.to as usize, &edit.insert);
+        }
+        assert_eq!(output, format_with_config(input, Config::default()).unwrap());
+
+        assert!(format_edits_with_config("x = 1\n", Config::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn format_edits_count_utf16_code_units() {
+        let input = "s = \"\u{1F600}é\"\nx=1\n";
+        let edits = format_edits_with_config(input, Config::default()).unwrap();
+
+        // The emoji is two code units and `é` one, so `x` starts at 10 rather than at 13.
+        assert_eq!(edits, [Edit { from: 10, to: 14, insert: "x = 1\n".to_string() }]);
+        assert_eq!(utf16_to_byte(input, edits[0].from).unwrap(), TextSize::new(13));
+    }
+
+    #[test]
+    fn collapse_single_subscript_comma_only_joins_one_element_subscripts() {
+        let config = Config::default().with_collapse_single_subscript_comma(true);
+        let run = |input: &str, config: Config| format_with_config(input, config).unwrap();
+
+        assert_eq!(run("x[\n    a,\n]\n", config.clone()), "x[a,]\n");
+        assert_eq!(run("x[a,]\n", config.clone()), "x[a,]\n");
+        assert_eq!(
+            run("y = x[\n    a[\n        b,\n    ],\n]\n", config.clone()),
+            "y = x[a[b,],]\n"
+        );
+
+        for input in [
+            "x[\n    a,\n    b,\n]\n",
+            "x[a, b,]\n",
+            "t = (\n    a,\n)\n",
+            "t = a,\n",
+            "x[(\n    a,\n)]\n",
+        ] {
+            assert_eq!(run(input, config.clone()), run(input, Config::default()), "{input}");
+        }
+    }
+
+    #[test]
+    fn collapse_single_subscript_comma_applies_to_range_formatting() {
+        let config = Config::default().with_collapse_single_subscript_comma(true);
+        let input = "a = x[\n    b,\n]\ny  =  x[\n    a,\n]\n";
+        let range = TextRange::new(TextSize::new(16), TextSize::new(17));
+
+        assert_eq!(
+            format_range_with_config(input, range, config).unwrap(),
+            "a = x[\n    b,\n]\ny = x[a,]\n"
+        );
+    }
+
+    #[test]
+    fn collapse_single_subscript_comma_keeps_suppressed_regions() {
+        let config = Config::default().with_collapse_single_subscript_comma(true);
+        let input = "# fmt: off\nx[\n    a,\n]\n# fmt: on\n";
+
+        assert_eq!(format_with_config(input, config).unwrap(), input);
+    }
+
+    #[test]
+    fn collapse_single_subscript_comma_measures_a_line_after_each_join() {
+        let config = Config::default()
+            .with_collapse_single_subscript_comma(true)
+            .with_line_width(NonZeroU16::new(8).unwrap());
+
+        // Either subscript fits on its own, but not both on one line.
+        assert_eq!(
+            crate::subscript_comma::apply("x[\n    a,\n][\n    b,\n]\n".to_string(), &config),
+            "x[a,][\n    b,\n]\n"
+        );
+    }
 }
@@ -1,15 +1,28 @@
+mod overrides;
+mod pyproject;
+mod schema;
+mod validate;
+
 use std::{
     num::{NonZeroU16, NonZeroU8},
+    ops::RangeInclusive,
     path::Path,
     str::FromStr,
 };
 
 use ruff_formatter::{printer::LineEnding as RuffLineEnding, IndentStyle as RuffIndentStyle};
-use ruff_python_formatter::{MagicTrailingComma, PyFormatOptions, QuoteStyle};
+use ruff_python_ast::PySourceType;
+use ruff_python_formatter::{
+    DocstringCode, DocstringCodeLineWidth, MagicTrailingComma, PreviewMode, PyFormatOptions,
+    PythonVersion, QuoteStyle,
+};
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Deserialize, Serialize)]
+pub use validate::{INDENT_WIDTH_RANGE, LINE_WIDTH_RANGE, TAB_WIDTH_RANGE};
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum IndentStyle {
     Tab,
@@ -46,18 +59,46 @@ impl From<IndentStyle> for RuffIndentStyle {
     }
 }
 
-#[derive(Clone, Default, Deserialize, Serialize)]
+#[derive(Clone, Default, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum LineEnding {
     #[default]
     Lf,
+    // `snake_case` would make this `cr_lf`, which is still accepted for configs written before.
+    #[serde(rename = "crlf", alias = "cr_lf")]
     CrLf,
+    /// Use whatever the input uses, see [`LineEnding::detect`].
+    Auto,
+}
+
+impl LineEnding {
+    /// Returns [`LineEnding::CrLf`] if every line of `input` ends with `\r\n`, and
+    /// [`LineEnding::Lf`] for LF-only, mixed or single-line input.
+    pub fn detect(input: &str) -> Self {
+        let crlf = input.matches("\r\n").count();
+        let lf = input.matches('\n').count();
+
+        if crlf > 0 && crlf == lf {
+            Self::CrLf
+        } else {
+            Self::Lf
+        }
+    }
+}
+
+impl From<RuffLineEnding> for LineEnding {
+    fn from(value: RuffLineEnding) -> Self {
+        match value {
+            RuffLineEnding::CarriageReturnLineFeed => Self::CrLf,
+            _ => Self::Lf,
+        }
+    }
 }
 
 impl From<LineEnding> for RuffLineEnding {
     fn from(value: LineEnding) -> Self {
         match value {
-            LineEnding::Lf => Self::LineFeed,
+            LineEnding::Lf | LineEnding::Auto => Self::LineFeed,
             LineEnding::CrLf => Self::CarriageReturnLineFeed,
         }
     }
@@ -70,24 +111,171 @@ impl FromStr for LineEnding {
         match s {
             "lf" => Ok(Self::Lf),
             "crlf" => Ok(Self::CrLf),
+            "auto" => Ok(Self::Auto),
             _ => Err("Value not supported for LineEnding"),
         }
     }
 }
 
-#[derive(Default, Clone, Deserialize, Serialize)]
+/// What kind of file is being formatted, for when it can't be inferred from its path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceType {
+    Python,
+    Stub,
+    Ipynb,
+}
+
+impl From<SourceType> for PySourceType {
+    fn from(value: SourceType) -> Self {
+        match value {
+            SourceType::Python => Self::Python,
+            SourceType::Stub => Self::Stub,
+            SourceType::Ipynb => Self::Ipynb,
+        }
+    }
+}
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetVersion {
+    Py37,
+    Py38,
+    Py39,
+    Py310,
+    Py311,
+    Py312,
+    Py313,
+}
+
+impl TargetVersion {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Py37 => "py37",
+            Self::Py38 => "py38",
+            Self::Py39 => "py39",
+            Self::Py310 => "py310",
+            Self::Py311 => "py311",
+            Self::Py312 => "py312",
+            Self::Py313 => "py313",
+        }
+    }
+}
+
+impl From<TargetVersion> for PythonVersion {
+    fn from(value: TargetVersion) -> Self {
+        match value {
+            TargetVersion::Py37 => Self::Py37,
+            TargetVersion::Py38 => Self::Py38,
+            TargetVersion::Py39 => Self::Py39,
+            TargetVersion::Py310 => Self::Py310,
+            TargetVersion::Py311 => Self::Py311,
+            TargetVersion::Py312 => Self::Py312,
+            TargetVersion::Py313 => Self::Py313,
+        }
+    }
+}
+
+impl From<PythonVersion> for TargetVersion {
+    fn from(value: PythonVersion) -> Self {
+        match value {
+            PythonVersion::Py37 => Self::Py37,
+            PythonVersion::Py38 => Self::Py38,
+            PythonVersion::Py39 => Self::Py39,
+            PythonVersion::Py310 => Self::Py310,
+            PythonVersion::Py311 => Self::Py311,
+            PythonVersion::Py312 => Self::Py312,
+            PythonVersion::Py313 => Self::Py313,
+        }
+    }
+}
+
+impl FromStr for TargetVersion {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "py37" => Ok(Self::Py37),
+            "py38" => Ok(Self::Py38),
+            "py39" => Ok(Self::Py39),
+            "py310" => Ok(Self::Py310),
+            "py311" => Ok(Self::Py311),
+            "py312" => Ok(Self::Py312),
+            "py313" => Ok(Self::Py313),
+            _ => Err("Value not supported for TargetVersion"),
+        }
+    }
+}
+
+/// How many columns a tab counts as when no `tab_width` is given.
+pub const DEFAULT_TAB_WIDTH: u8 = 8;
+
+/// The number of blank lines ruff's formatter keeps between top-level statements.
+pub const BLANK_LINES_RANGE: RangeInclusive<u8> = 1..=2;
+
+#[derive(Default, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct Config {
     #[serde(alias = "indentStyle")]
     pub indent_style: Option<IndentStyle>,
+    // The schema bounds mirror `INDENT_WIDTH_RANGE`, `TAB_WIDTH_RANGE` and `LINE_WIDTH_RANGE`;
+    // the attribute only takes literals.
     #[serde(alias = "indentWidth")]
+    #[schemars(range(min = 1, max = 24))]
     pub indent_width: Option<NonZeroU8>,
+    #[serde(alias = "tabWidth")]
+    #[schemars(range(min = 1, max = 16))]
+    pub tab_width: Option<NonZeroU8>,
     #[serde(alias = "lineWidth")]
+    #[schemars(range(min = 1, max = 320))]
     pub line_width: Option<NonZeroU16>,
     #[serde(alias = "lineEnding")]
     pub line_ending: Option<LineEnding>,
 
     pub quote_style: Option<QuoteStyle>,
+    #[serde(alias = "docstringQuoteStyle")]
+    pub docstring_quote_style: Option<QuoteStyle>,
     pub magic_trailing_comma: Option<MagicTrailingComma>,
+    #[serde(alias = "skipMagicTrailingComma")]
+    pub skip_magic_trailing_comma: Option<bool>,
+    #[serde(alias = "collapseSingleSubscriptComma")]
+    pub collapse_single_subscript_comma: Option<bool>,
+
+    #[serde(alias = "targetVersion")]
+    pub target_version: Option<TargetVersion>,
+    #[serde(alias = "sourceType")]
+    pub source_type: Option<SourceType>,
+
+    #[serde(alias = "normalizeNumericLiterals")]
+    pub normalize_numeric_literals: Option<bool>,
+
+    #[serde(alias = "docstringCodeFormat")]
+    pub docstring_code_format: Option<bool>,
+    #[serde(alias = "docstringCodeLineWidth")]
+    pub docstring_code_line_width: Option<DocstringCodeLineWidth>,
+
+    pub preview: Option<bool>,
+
+    #[serde(alias = "blankLinesTopLevel")]
+    pub blank_lines_top_level: Option<i32>,
+    #[serde(alias = "blankLinesAfterImports")]
+    pub blank_lines_after_imports: Option<i32>,
+
+    #[serde(alias = "insertFinalNewline")]
+    pub insert_final_newline: Option<bool>,
+
+    #[serde(alias = "organizeImports")]
+    pub organize_imports: Option<bool>,
+    #[serde(alias = "knownFirstParty")]
+    pub known_first_party: Option<Vec<String>>,
+    #[serde(alias = "combineAsImports")]
+    pub combine_as_imports: Option<bool>,
+
+    pub select: Option<Vec<String>>,
+    pub ignore: Option<Vec<String>>,
+    #[serde(alias = "unsafeFixes")]
+    pub unsafe_fixes: Option<bool>,
 
     #[serde(skip)]
     path: String,
@@ -104,6 +292,11 @@ impl Config {
         self
     }
 
+    pub fn with_tab_width(mut self, tab_width: NonZeroU8) -> Self {
+        self.tab_width = Some(tab_width);
+        self
+    }
+
     pub fn with_line_width(mut self, line_width: NonZeroU16) -> Self {
         self.line_width = Some(line_width);
         self
@@ -114,31 +307,243 @@ impl Config {
         self
     }
 
+    /// Resolves an `auto` line ending against the document that is about to be formatted.
+    pub fn with_detected_line_ending(mut self, input: &str) -> Self {
+        if let Some(LineEnding::Auto) = self.line_ending {
+            self.line_ending = Some(LineEnding::detect(input));
+        }
+        self
+    }
+
     pub fn with_quote_style(mut self, quote_style: QuoteStyle) -> Self {
         self.quote_style = Some(quote_style);
         self
     }
 
+    pub fn with_docstring_quote_style(mut self, docstring_quote_style: QuoteStyle) -> Self {
+        self.docstring_quote_style = Some(docstring_quote_style);
+        self
+    }
+
     pub fn with_magic_trailing_comma(mut self, magic_trailing_comma: MagicTrailingComma) -> Self {
         self.magic_trailing_comma = Some(magic_trailing_comma);
         self
     }
 
+    pub fn with_skip_magic_trailing_comma(mut self, skip_magic_trailing_comma: bool) -> Self {
+        self.skip_magic_trailing_comma = Some(skip_magic_trailing_comma);
+        self
+    }
+
+    pub fn with_collapse_single_subscript_comma(
+        mut self,
+        collapse_single_subscript_comma: bool,
+    ) -> Self {
+        self.collapse_single_subscript_comma = Some(collapse_single_subscript_comma);
+        self
+    }
+
+    pub fn with_target_version(mut self, target_version: TargetVersion) -> Self {
+        self.target_version = Some(target_version);
+        self
+    }
+
+    pub fn with_source_type(mut self, source_type: SourceType) -> Self {
+        self.source_type = Some(source_type);
+        self
+    }
+
+    pub fn with_normalize_numeric_literals(mut self, normalize_numeric_literals: bool) -> Self {
+        self.normalize_numeric_literals = Some(normalize_numeric_literals);
+        self
+    }
+
+    pub fn with_docstring_code_format(mut self, docstring_code_format: bool) -> Self {
+        self.docstring_code_format = Some(docstring_code_format);
+        self
+    }
+
+    pub fn with_docstring_code_line_width(
+        mut self,
+        docstring_code_line_width: DocstringCodeLineWidth,
+    ) -> Self {
+        self.docstring_code_line_width = Some(docstring_code_line_width);
+        self
+    }
+
+    pub fn with_preview(mut self, preview: bool) -> Self {
+        self.preview = Some(preview);
+        self
+    }
+
+    pub fn with_blank_lines_top_level(mut self, blank_lines_top_level: i32) -> Self {
+        self.blank_lines_top_level = Some(blank_lines_top_level);
+        self
+    }
+
+    pub fn with_blank_lines_after_imports(mut self, blank_lines_after_imports: i32) -> Self {
+        self.blank_lines_after_imports = Some(blank_lines_after_imports);
+        self
+    }
+
+    pub fn with_insert_final_newline(mut self, insert_final_newline: bool) -> Self {
+        self.insert_final_newline = Some(insert_final_newline);
+        self
+    }
+
+    pub fn with_organize_imports(mut self, organize_imports: bool) -> Self {
+        self.organize_imports = Some(organize_imports);
+        self
+    }
+
+    pub fn with_known_first_party(mut self, known_first_party: Vec<String>) -> Self {
+        self.known_first_party = Some(known_first_party);
+        self
+    }
+
+    pub fn with_combine_as_imports(mut self, combine_as_imports: bool) -> Self {
+        self.combine_as_imports = Some(combine_as_imports);
+        self
+    }
+
+    pub fn with_select(mut self, select: Vec<String>) -> Self {
+        self.select = Some(select);
+        self
+    }
+
+    pub fn with_ignore(mut self, ignore: Vec<String>) -> Self {
+        self.ignore = Some(ignore);
+        self
+    }
+
+    pub fn with_unsafe_fixes(mut self, unsafe_fixes: bool) -> Self {
+        self.unsafe_fixes = Some(unsafe_fixes);
+        self
+    }
+
     pub fn with_path(mut self, path: String) -> Self {
         self.path = path;
         self
     }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// `blank_lines_top_level`, clamped to [`BLANK_LINES_RANGE`].
+    pub fn blank_lines_top_level(&self) -> Option<u8> {
+        self.blank_lines_top_level.map(clamp_blank_lines)
+    }
+
+    /// `blank_lines_after_imports`, clamped to [`BLANK_LINES_RANGE`].
+    pub fn blank_lines_after_imports(&self) -> Option<u8> {
+        self.blank_lines_after_imports.map(clamp_blank_lines)
+    }
+
+    /// Non-fatal problems with this config, such as values that had to be clamped.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings: Vec<String> = [
+            ("blank_lines_top_level", self.blank_lines_top_level),
+            ("blank_lines_after_imports", self.blank_lines_after_imports),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let value = value?;
+            let clamped = clamp_blank_lines(value);
+
+            (i32::from(clamped) != value).then(|| {
+                format!(
+                    "`{name}` must be between {} and {}, using {clamped} instead of {value}",
+                    BLANK_LINES_RANGE.start(),
+                    BLANK_LINES_RANGE.end()
+                )
+            })
+        })
+        .collect();
+
+        if let (Some(comma), Some(skip)) =
+            (self.magic_trailing_comma, self.skip_magic_trailing_comma)
+        {
+            if (comma == MagicTrailingComma::Ignore) != skip {
+                warnings.push(
+                    "`skip_magic_trailing_comma` is ignored because `magic_trailing_comma` is set"
+                        .to_string(),
+                );
+            }
+        }
+
+        warnings
+    }
+}
+
+fn clamp_blank_lines(value: i32) -> u8 {
+    let (min, max) = (*BLANK_LINES_RANGE.start(), *BLANK_LINES_RANGE.end());
+    value.clamp(min.into(), max.into()) as u8
+}
+
+impl Config {
+    /// The value every option takes when it is left unset.
+    ///
+    /// Unlike [`Config::default`], which leaves every field `None`, this spells out ruff's
+    /// defaults so a settings UI can show them.
+    ///
+    /// The trailing comma default is given as `skip_magic_trailing_comma`: a set
+    /// `magic_trailing_comma` wins over it, so a form seeded with both couldn't toggle the
+    /// checkbox.
+    pub fn defaults() -> Self {
+        let mut defaults = Self::from(&PyFormatOptions::default())
+            .with_tab_width(NonZeroU8::new(DEFAULT_TAB_WIDTH).unwrap())
+            .with_insert_final_newline(true)
+            .with_normalize_numeric_literals(true)
+            .with_organize_imports(false)
+            .with_combine_as_imports(false)
+            .with_unsafe_fixes(false);
+
+        defaults.skip_magic_trailing_comma =
+            defaults.magic_trailing_comma.take().map(|comma| comma == MagicTrailingComma::Ignore);
+
+        defaults
+    }
+}
+
+impl From<&PyFormatOptions> for Config {
+    fn from(value: &PyFormatOptions) -> Self {
+        Self {
+            indent_style: Some(value.indent_style().into()),
+            indent_width: u8::try_from(value.indent_width().value()).ok().and_then(NonZeroU8::new),
+            line_width: NonZeroU16::new(value.line_width().value()),
+            line_ending: Some(value.line_ending().into()),
+            quote_style: Some(value.quote_style()),
+            magic_trailing_comma: Some(value.magic_trailing_comma()),
+            target_version: Some(value.target_version().into()),
+            docstring_code_format: Some(value.docstring_code().is_enabled()),
+            docstring_code_line_width: Some(value.docstring_code_line_width()),
+            preview: Some(value.preview().is_enabled()),
+            ..Default::default()
+        }
+    }
 }
 
 impl From<Config> for PyFormatOptions {
     fn from(value: Config) -> Self {
-        let mut config = Self::from_extension(Path::new(&value.path));
+        let mut config = match value.source_type {
+            Some(source_type) => Self::from_source_type(source_type.into()),
+            None => Self::from_extension(Path::new(&value.path)),
+        };
 
         if let Some(indent_style) = value.indent_style {
             config = config.with_indent_style(indent_style.into());
         }
 
-        if let Some(indent_width) = value.indent_width {
+        // Ruff has no separate tab width: with tab indentation, `indent_width` is only used to
+        // measure how wide a tab is when deciding whether a line fits.
+        let indent_width = if value.indent_style == Some(IndentStyle::Tab) {
+            value.tab_width.or(value.indent_width).or(NonZeroU8::new(DEFAULT_TAB_WIDTH))
+        } else {
+            value.indent_width
+        };
+
+        if let Some(indent_width) = indent_width {
             config = config.with_indent_width(indent_width.into());
         }
 
@@ -154,10 +559,44 @@ impl From<Config> for PyFormatOptions {
             config = config.with_quote_style(quote_style);
         }
 
-        if let Some(magic_trailing_comma) = value.magic_trailing_comma {
+        let magic_trailing_comma = value.magic_trailing_comma.or_else(|| {
+            value.skip_magic_trailing_comma.map(|skip| {
+                if skip {
+                    MagicTrailingComma::Ignore
+                } else {
+                    MagicTrailingComma::Respect
+                }
+            })
+        });
+
+        if let Some(magic_trailing_comma) = magic_trailing_comma {
             config = config.with_magic_trailing_comma(magic_trailing_comma);
         }
 
+        if let Some(target_version) = value.target_version {
+            config = config.with_target_version(target_version.into());
+        }
+
+        if let Some(docstring_code_format) = value.docstring_code_format {
+            config = config.with_docstring_code(if docstring_code_format {
+                DocstringCode::Enabled
+            } else {
+                DocstringCode::Disabled
+            });
+        }
+
+        if let Some(docstring_code_line_width) = value.docstring_code_line_width {
+            config = config.with_docstring_code_line_width(docstring_code_line_width);
+        }
+
+        if let Some(preview) = value.preview {
+            config = config.with_preview(if preview {
+                PreviewMode::Enabled
+            } else {
+                PreviewMode::Disabled
+            });
+        }
+
         config
     }
 }
@@ -0,0 +1,46 @@
+use std::num::{NonZeroU16, NonZeroU8};
+
+use crate::{Config, INDENT_WIDTH_RANGE, LINE_WIDTH_RANGE};
+
+impl Config {
+    /// Applies the per-file overrides found in the leading comments of `input`.
+    ///
+    /// An override is a comment of the form `# ruff: <key> = <value>` before the first line of
+    /// code; shebangs, encoding declarations and other comments may come before or after it. The
+    /// recognized keys are:
+    ///
+    /// - `line-length`: overrides `line_width`
+    /// - `indent-width`: overrides `indent_width`
+    ///
+    /// Other keys, such as `# ruff: noqa`, and out-of-range values are ignored. A leading byte
+    /// order mark is skipped.
+    pub fn with_file_overrides(mut self, input: &str) -> Self {
+        let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+        let comments = input
+            .lines()
+            .map(str::trim)
+            .take_while(|line| line.is_empty() || line.starts_with('#'))
+            .filter_map(|line| line.strip_prefix('#')?.trim_start().strip_prefix("ruff:"));
+
+        for comment in comments {
+            let Some((key, value)) = comment.split_once('=') else {
+                continue;
+            };
+            let Ok(value) = value.trim().parse::<u16>() else {
+                continue;
+            };
+
+            match key.trim() {
+                "line-length" if LINE_WIDTH_RANGE.contains(&value) => {
+                    self.line_width = NonZeroU16::new(value);
+                }
+                "indent-width" if INDENT_WIDTH_RANGE.contains(&value) => {
+                    self.indent_width = u8::try_from(value).ok().and_then(NonZeroU8::new);
+                }
+                _ => {}
+            }
+        }
+
+        self
+    }
+}
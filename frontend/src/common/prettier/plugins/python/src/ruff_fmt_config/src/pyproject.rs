@@ -0,0 +1,96 @@
+use std::num::{NonZeroU16, NonZeroU8};
+
+use ruff_python_formatter::{DocstringCodeLineWidth, QuoteStyle};
+use serde::Deserialize;
+
+use crate::{Config, IndentStyle, LineEnding, TargetVersion};
+
+#[derive(Default, Deserialize)]
+struct PyProject {
+    #[serde(default)]
+    tool: Tool,
+}
+
+#[derive(Default, Deserialize)]
+struct Tool {
+    #[serde(default)]
+    ruff: Ruff,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct Ruff {
+    line_length: Option<NonZeroU16>,
+    indent_width: Option<NonZeroU8>,
+    target_version: Option<TargetVersion>,
+    preview: Option<bool>,
+
+    #[serde(default)]
+    format: RuffFormat,
+    #[serde(default)]
+    lint: RuffLint,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RuffFormat {
+    indent_style: Option<IndentStyle>,
+    quote_style: Option<QuoteStyle>,
+    line_ending: Option<String>,
+    skip_magic_trailing_comma: Option<bool>,
+    docstring_code_format: Option<bool>,
+    docstring_code_line_length: Option<DocstringCodeLineWidth>,
+    preview: Option<bool>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RuffLint {
+    select: Option<Vec<String>>,
+    ignore: Option<Vec<String>>,
+
+    #[serde(default)]
+    isort: RuffIsort,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RuffIsort {
+    known_first_party: Option<Vec<String>>,
+    combine_as_imports: Option<bool>,
+}
+
+impl Config {
+    /// Reads the `[tool.ruff]` settings of a `pyproject.toml`.
+    ///
+    /// Unknown keys and missing tables are ignored, so a file without ruff settings yields the
+    /// default config.
+    pub fn from_pyproject(toml: &str) -> Result<Self, String> {
+        let pyproject: PyProject = toml::from_str(toml).map_err(|err| err.to_string())?;
+        let Ruff { line_length, indent_width, target_version, preview, format, lint } =
+            pyproject.tool.ruff;
+
+        Ok(Self {
+            indent_style: format.indent_style,
+            indent_width,
+            line_width: line_length,
+            line_ending: format.line_ending.as_deref().and_then(|line_ending| match line_ending {
+                "lf" => Some(LineEnding::Lf),
+                "cr-lf" => Some(LineEnding::CrLf),
+                "auto" => Some(LineEnding::Auto),
+                _ => None,
+            }),
+            quote_style: format.quote_style,
+            skip_magic_trailing_comma: format.skip_magic_trailing_comma,
+            target_version,
+            docstring_code_format: format.docstring_code_format,
+            docstring_code_line_width: format.docstring_code_line_length,
+            preview: format.preview.or(preview),
+            known_first_party: lint.isort.known_first_party,
+            combine_as_imports: lint.isort.combine_as_imports,
+            select: lint.select,
+            ignore: lint.ignore,
+            ..Default::default()
+        })
+    }
+}
@@ -0,0 +1,36 @@
+use schemars::gen::SchemaSettings;
+use serde_json::Value;
+
+use crate::Config;
+
+const META_SCHEMA: &str = "https://json-schema.org/draft/2020-12/schema";
+
+impl Config {
+    /// A JSON Schema describing every field of a [`Config`], with [`Config::defaults`] as the
+    /// `default` of each property.
+    ///
+    /// The schema is derived from the same types serde uses, so it can't drift from what the
+    /// formatter accepts. It's generated in the 2019-09 dialect, which only differs from 2020-12
+    /// in tuple and recursive schemas that `Config` doesn't use, and declares 2020-12.
+    pub fn schema() -> Value {
+        let mut settings = SchemaSettings::draft2019_09();
+        settings.meta_schema = Some(META_SCHEMA.to_string());
+
+        let schema = settings.into_generator().into_root_schema_for::<Config>();
+        let mut schema = serde_json::to_value(schema).expect("a schema is valid JSON");
+
+        let defaults = serde_json::to_value(Config::defaults()).expect("a config is valid JSON");
+
+        if let (Some(properties), Value::Object(defaults)) =
+            (schema.get_mut("properties").and_then(Value::as_object_mut), defaults)
+        {
+            for (name, default) in defaults.into_iter().filter(|(_, value)| !value.is_null()) {
+                if let Some(Value::Object(property)) = properties.get_mut(&name) {
+                    property.insert("default".to_string(), default);
+                }
+            }
+        }
+
+        schema
+    }
+}
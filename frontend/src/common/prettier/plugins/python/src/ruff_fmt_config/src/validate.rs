@@ -0,0 +1,100 @@
+use std::ops::RangeInclusive;
+
+use serde_json::{Map, Value};
+
+use crate::Config;
+
+pub const INDENT_WIDTH_RANGE: RangeInclusive<u16> = 1..=24;
+pub const LINE_WIDTH_RANGE: RangeInclusive<u16> = 1..=320;
+pub const TAB_WIDTH_RANGE: RangeInclusive<u16> = 1..=16;
+
+/// The fields `check_integer` covers, under both spellings.
+const INTEGER_FIELDS: [&str; 8] = [
+    "indent_width",
+    "indentWidth",
+    "tab_width",
+    "tabWidth",
+    "line_width",
+    "lineWidth",
+    "docstring_code_line_width",
+    "docstringCodeLineWidth",
+];
+
+impl Config {
+    /// Checks a user-provided config before it is handed to the formatter.
+    ///
+    /// `value` is the raw JSON form of a [`Config`]. Every invalid field contributes one line to
+    /// the error, so a settings form can show all problems at once.
+    pub fn validate(value: &Value) -> Result<(), String> {
+        let Some(object) = value.as_object() else {
+            return Err("config must be an object".to_string());
+        };
+
+        let mut errors = vec![];
+
+        check_integer(object, "indent_width", "indentWidth", INDENT_WIDTH_RANGE, &mut errors);
+        check_integer(object, "tab_width", "tabWidth", TAB_WIDTH_RANGE, &mut errors);
+        check_integer(object, "line_width", "lineWidth", LINE_WIDTH_RANGE, &mut errors);
+
+        let docstring_code_line_width =
+            field(object, "docstring_code_line_width", "docstringCodeLineWidth");
+        if !matches!(docstring_code_line_width, Some(Value::String(value)) if value == "dynamic") {
+            check_integer(
+                object,
+                "docstring_code_line_width",
+                "docstringCodeLineWidth",
+                LINE_WIDTH_RANGE,
+                &mut errors,
+            );
+        }
+
+        // Let serde report anything else, e.g. an unknown `quote_style`. It stops at the first
+        // bad field, so each one is deserialized on its own.
+        for (key, value) in object {
+            if value.is_null() || INTEGER_FIELDS.contains(&key.as_str()) {
+                continue;
+            }
+
+            let field = Map::from_iter([(key.clone(), value.clone())]);
+            if let Err(err) = serde_json::from_value::<Config>(Value::Object(field)) {
+                errors.push(format!("`{key}`: {err}"));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+}
+
+fn field<'a>(object: &'a Map<String, Value>, name: &str, alias: &str) -> Option<&'a Value> {
+    object.get(name).or_else(|| object.get(alias)).filter(|value| !value.is_null())
+}
+
+fn check_integer(
+    object: &Map<String, Value>,
+    name: &str,
+    alias: &str,
+    range: RangeInclusive<u16>,
+    errors: &mut Vec<String>,
+) {
+    let Some(value) = field(object, name, alias) else {
+        return;
+    };
+
+    let in_range = value.as_f64().is_some_and(|value| {
+        value.fract() == 0.0
+            && value >= f64::from(*range.start())
+            && value <= f64::from(*range.end())
+    });
+
+    if !in_range {
+        errors.push(format!(
+            "`{name}` must be an integer between {} and {}, got {value}",
+            range.start(),
+            range.end()
+        ));
+    }
+}